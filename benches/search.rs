@@ -0,0 +1,135 @@
+#[macro_use]
+extern crate criterion;
+extern crate knuth_morris_pratt;
+
+use criterion::{black_box, Criterion};
+
+use knuth_morris_pratt::knuth_morris_pratt;
+
+// some text with a pattern at the end; `pattern_len` decides whether the
+// shift table is on the stack (below 32 elements) or on the heap
+fn text_with_pattern(text_len: usize, pattern_len: usize) -> (String, String) {
+    let word = "lorem ipsum dolor sit amet ";
+    let mut text = word.repeat(text_len / word.len() + 1);
+    text.truncate(text_len);
+    let pattern = word.chars().cycle().skip(3).take(pattern_len - 1)
+                      .chain(Some('!')).collect::<String>();
+    text.push_str(&pattern);
+    (text, pattern)
+}
+
+fn short_pattern(c: &mut Criterion) {
+    let (text, pattern) = text_with_pattern(10_000, 16);
+    c.bench_function("short pattern (stack table)", |b| b.iter(|| {
+        knuth_morris_pratt(black_box(text.as_bytes()), black_box(pattern.as_bytes()))
+    }));
+}
+
+fn long_pattern(c: &mut Criterion) {
+    let (text, pattern) = text_with_pattern(10_000, 64);
+    c.bench_function("long pattern (heap table)", |b| b.iter(|| {
+        knuth_morris_pratt(black_box(text.as_bytes()), black_box(pattern.as_bytes()))
+    }));
+}
+
+fn single_element(c: &mut Criterion) {
+    let (text, _) = text_with_pattern(10_000, 1);
+    c.bench_function("single element pattern", |b| b.iter(|| {
+        knuth_morris_pratt(black_box(text.as_bytes()), black_box(b"!"))
+    }));
+}
+
+fn periodic_worst_case(c: &mut Criterion) {
+    let text = "a".repeat(10_000);
+    let pattern = "a".repeat(100) + "b";
+    c.bench_function("periodic a..ab in a..a", |b| b.iter(|| {
+        knuth_morris_pratt(black_box(text.as_bytes()), black_box(pattern.as_bytes()))
+    }));
+    c.bench_function("periodic a..ab in a..a (str::find)", |b| b.iter(|| {
+        black_box(&text[..]).find(black_box(&pattern[..]))
+    }));
+}
+
+fn str_find_baseline(c: &mut Criterion) {
+    let (text, pattern) = text_with_pattern(10_000, 16);
+    c.bench_function("short pattern (str::find)", |b| b.iter(|| {
+        black_box(&text[..]).find(black_box(&pattern[..]))
+    }));
+    let (text, pattern) = text_with_pattern(10_000, 64);
+    c.bench_function("long pattern (str::find)", |b| b.iter(|| {
+        black_box(&text[..]).find(black_box(&pattern[..]))
+    }));
+}
+
+#[cfg(all(feature = "memchr", feature = "alloc"))]
+fn memchr_english(c: &mut Criterion) {
+    use knuth_morris_pratt::{knuth_morris_pratt_bytes, Searcher};
+
+    let paragraph = "It was the best of times, it was the worst of times, it was the \
+                     age of wisdom, it was the age of foolishness, it was the epoch of \
+                     belief, it was the epoch of incredulity, it was the season of \
+                     Light, it was the season of Darkness. ";
+    let text = paragraph.repeat(100) + "Zounds";
+    let pattern = "Zounds";
+    c.bench_function("english text (memchr)", |b| b.iter(|| {
+        knuth_morris_pratt_bytes(black_box(text.as_bytes()), black_box(pattern.as_bytes()))
+    }));
+    c.bench_function("english text", |b| b.iter(|| {
+        knuth_morris_pratt(black_box(text.as_bytes()), black_box(pattern.as_bytes()))
+    }));
+    c.bench_function("english text, single element (memchr)", |b| b.iter(|| {
+        knuth_morris_pratt_bytes(black_box(text.as_bytes()), black_box(b"Z"))
+    }));
+
+    let searcher = Searcher::new(b"season".to_vec());
+    c.bench_function("english text, all matches (searcher, memchr)", |b| b.iter(|| {
+        searcher.find_iter_bytes(black_box(text.as_bytes())).count()
+    }));
+    c.bench_function("english text, all matches (searcher)", |b| b.iter(|| {
+        searcher.find_all(black_box(text.as_bytes())).count()
+    }));
+}
+
+#[cfg(not(all(feature = "memchr", feature = "alloc")))]
+fn memchr_english(_: &mut Criterion) { }
+
+#[cfg(feature = "std")]
+fn bmh_large_alphabet(c: &mut Criterion) {
+    use knuth_morris_pratt::knuth_morris_pratt_bmh;
+
+    let mut text = (0..10_000u64).map(|x| x.wrapping_mul(0x9e37_79b9_7f4a_7c15)).collect::<Vec<_>>();
+    let pattern = (0..16u64).collect::<Vec<_>>();
+    text.extend(&pattern);
+    c.bench_function("large alphabet (skip table)", |b| b.iter(|| {
+        knuth_morris_pratt_bmh(black_box(&text), black_box(&pattern))
+    }));
+    c.bench_function("large alphabet", |b| b.iter(|| {
+        knuth_morris_pratt(black_box(&text), black_box(&pattern))
+    }));
+}
+
+#[cfg(not(feature = "std"))]
+fn bmh_large_alphabet(_: &mut Criterion) { }
+
+#[cfg(feature = "rayon")]
+fn par_contains(c: &mut Criterion) {
+    use knuth_morris_pratt::{knuth_morris_pratt_contains, knuth_morris_pratt_par_contains};
+
+    let (text, pattern) = text_with_pattern(10_000, 16);
+    let texts = (0..1000).map(|i| &text.as_bytes()[i..]).collect::<Vec<_>>();
+    c.bench_function("contains, 1000 texts (rayon)", |b| b.iter(|| {
+        knuth_morris_pratt_par_contains(black_box(&texts), black_box(pattern.as_bytes()))
+    }));
+    c.bench_function("contains, 1000 texts (sequential)", |b| b.iter(|| {
+        black_box(&texts).iter()
+            .map(|text| knuth_morris_pratt_contains(text, black_box(pattern.as_bytes())))
+            .collect::<Vec<_>>()
+    }));
+}
+
+#[cfg(not(feature = "rayon"))]
+fn par_contains(_: &mut Criterion) { }
+
+criterion_group!(benches, short_pattern, long_pattern, single_element, periodic_worst_case,
+                 str_find_baseline, par_contains, memchr_english, bmh_large_alphabet);
+criterion_main!(benches);
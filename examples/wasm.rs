@@ -0,0 +1,79 @@
+//! A thin C ABI wrapper, for example for calling the search from JavaScript
+//! in a WebAssembly module.
+//!
+//! Build it with:
+//!
+//! ```text
+//! rustup target add wasm32-unknown-unknown
+//! cargo build --release --example wasm --target wasm32-unknown-unknown \
+//!     --no-default-features --features alloc
+//! ```
+//!
+//! and load `target/wasm32-unknown-unknown/release/examples/wasm.wasm`. The
+//! caller copies the text and the pattern into the memory of the module,
+//! using `kmp_alloc` and `kmp_free`, and passes pointer and length pairs.
+
+extern crate knuth_morris_pratt;
+
+use std::mem;
+use std::slice;
+
+use knuth_morris_pratt::{knuth_morris_pratt, knuth_morris_pratt_count};
+
+/// Allocate `len` bytes, for the caller to fill in.
+#[no_mangle]
+pub extern "C" fn kmp_alloc(len: usize) -> *mut u8 {
+    let mut buf = Vec::with_capacity(len);
+    let ptr = buf.as_mut_ptr();
+    mem::forget(buf);
+    ptr
+}
+
+/// Free `len` bytes allocated with `kmp_alloc`.
+///
+/// # Safety
+///
+/// `ptr` and `len` must be from the same call of `kmp_alloc`.
+#[no_mangle]
+pub unsafe extern "C" fn kmp_free(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Return the byte offset of the first occurence of the pattern in the text,
+/// or `-1` if it is not found.
+///
+/// # Safety
+///
+/// The pointers must point to `text_len` and `pattern_len` initialized
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kmp_find(text: *const u8, text_len: usize,
+                                  pattern: *const u8, pattern_len: usize) -> isize {
+    let (text, pattern) = (bytes(text, text_len), bytes(pattern, pattern_len));
+    match knuth_morris_pratt(text, pattern) {
+        Some(start) => start as isize,
+        None => -1,
+    }
+}
+
+/// Return the number of non-overlapping occurences of the pattern in the
+/// text.
+///
+/// # Safety
+///
+/// The pointers must point to `text_len` and `pattern_len` initialized
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kmp_count(text: *const u8, text_len: usize,
+                                   pattern: *const u8, pattern_len: usize) -> usize {
+    knuth_morris_pratt_count(bytes(text, text_len), bytes(pattern, pattern_len))
+}
+
+// a null pointer is allowed for an empty slice
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len)
+    }
+}
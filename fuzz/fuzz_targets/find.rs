@@ -0,0 +1,27 @@
+//! Check `knuth_morris_pratt` against a naive search.
+//!
+//! Run with `cargo fuzz run find` in the crate directory (requires nightly).
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate knuth_morris_pratt;
+
+use knuth_morris_pratt::knuth_morris_pratt;
+
+/// O(nm) reference search: the first offset where `pattern` matches `text`.
+fn naive_find(text: &[u8], pattern: &[u8]) -> Option<usize> {
+    if pattern.len() > text.len() {
+        return None;
+    }
+    (0..text.len() - pattern.len() + 1).find(|&i| &text[i..i + pattern.len()] == pattern)
+}
+
+fuzz_target!(|data: &[u8]| {
+    // first byte selects where the input is split into pattern and text
+    if let Some((&split, rest)) = data.split_first() {
+        let split = split as usize % (rest.len() + 1);
+        let (pattern, text) = rest.split_at(split);
+        assert_eq!(knuth_morris_pratt(text, pattern), naive_find(text, pattern),
+                   "text={:?} pattern={:?}", text, pattern);
+    }
+});
@@ -0,0 +1,184 @@
+//! A KMP automaton that is fed one element at a time.
+
+use alloc::vec::Vec;
+
+use {feed, NextTable};
+
+/// A KMP automaton, for searching a text that is fed to it one element at a
+/// time.
+///
+/// The automaton owns the pattern and its shift table and keeps track of
+/// how much of the pattern is matched by the most recent elements. Matches
+/// do not overlap: after a match, matching starts over from the beginning
+/// of the pattern.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use knuth_morris_pratt::Automaton;
+///
+/// let mut automaton = Automaton::new(b"abc".to_vec());
+/// let ends = b"xabcabc".iter().filter_map(|b| automaton.push(b)).collect::<Vec<_>>();
+/// assert_eq!(ends, vec![4, 7]);
+/// ```
+pub struct Automaton<T> {
+    pattern: Vec<T>,
+    next: NextTable,
+    // matched length of the pattern
+    i: usize,
+    // number of elements fed
+    count: usize,
+}
+
+impl<T> Automaton<T> {
+    /// Create a new automaton for `pattern`.
+    pub fn new(pattern: Vec<T>) -> Self
+        where T: PartialEq
+    {
+        Automaton::new_by(pattern, PartialEq::eq)
+    }
+
+    /// Create a new automaton for `pattern`.
+    ///
+    /// Use the function `equal` for equality comparison when computing the
+    /// shift table.
+    pub fn new_by<FEq>(pattern: Vec<T>, mut equal: FEq) -> Self
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        let next = NextTable::new(&pattern[..], &mut equal);
+        Automaton {
+            pattern,
+            next,
+            i: 0,
+            count: 0,
+        }
+    }
+
+    /// Feed the next element of the text to the automaton.
+    ///
+    /// If it completes a match, return the end of the match inside a `Some`,
+    /// as the number of elements fed so far. Otherwise, return `None`.
+    ///
+    /// The empty pattern matches after every element.
+    pub fn push(&mut self, elem: &T) -> Option<usize>
+        where T: PartialEq
+    {
+        self.push_by(elem, PartialEq::eq)
+    }
+
+    /// Feed the next element of the text to the automaton.
+    ///
+    /// If it completes a match, return the end of the match inside a `Some`,
+    /// as the number of elements fed so far. Otherwise, return `None`.
+    ///
+    /// Use the function `equal` for equality comparison. It must agree with
+    /// the equality used to create the automaton, when comparing elements of
+    /// the pattern with each other.
+    pub fn push_by<FEq>(&mut self, elem: &T, mut equal: FEq) -> Option<usize>
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        self.count += 1;
+        if self.pattern.is_empty() {
+            return Some(self.count);
+        }
+        if feed(elem, &self.pattern, &self.next, &mut self.i, &mut equal) {
+            self.i = 0;
+            Some(self.count)
+        } else {
+            None
+        }
+    }
+
+    /// Return the length of the prefix of the pattern that the most recent
+    /// elements match: the length of the longest prefix of the pattern that
+    /// is a suffix of the elements fed since the last match.
+    ///
+    /// A match that is not complete yet starts within the last
+    /// `partial_match_len()` elements fed, so a caller that processes the
+    /// text in chunks can keep just those elements of a chunk before
+    /// discarding it, and no match across the boundary to the next chunk is
+    /// missed.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::Automaton;
+    ///
+    /// let mut automaton = Automaton::new(b"abc".to_vec());
+    /// for b in b"xxab" {
+    ///     automaton.push(b);
+    /// }
+    /// assert_eq!(automaton.partial_match_len(), 2);
+    /// ```
+    pub fn partial_match_len(&self) -> usize {
+        self.i
+    }
+
+    /// Clear the automaton's state, as if no elements had been fed.
+    pub fn reset(&mut self) {
+        self.i = 0;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push() {
+        let mut automaton = Automaton::new(b"aa".to_vec());
+        let ends = b"aaaaba".iter().map(|b| automaton.push(b)).collect::<Vec<_>>();
+        assert_eq!(ends, vec![None, Some(2), None, Some(4), None, None]);
+    }
+
+    #[test]
+    fn test_push_by() {
+        let body = [[1, 2], [3, 4], [5, 6], [3, 4], [5, 10]];
+        let sum_eq = |a: &[i32; 2], b: &[i32; 2]| a[0] + a[1] == b[0] + b[1];
+        let mut automaton = Automaton::new_by(vec![[7, 0], [12, 3]], sum_eq);
+        let ends = body.iter().filter_map(|x| automaton.push_by(x, sum_eq)).collect::<Vec<_>>();
+        assert_eq!(ends, vec![5]);
+    }
+
+    #[test]
+    fn test_partial_match_len_chunks() {
+        use {knuth_morris_pratt, knuth_morris_pratt_all};
+
+        let pattern = b"abcab";
+        let chunks: [&[u8]; 4] = [b"xxabcaba", b"bcabx", b"a", b"bcab"];
+        let text = chunks.concat();
+        let mut automaton = Automaton::new(pattern.to_vec());
+        // the elements kept from the previous chunks, and the stream offset
+        // of the first of them
+        let mut kept = Vec::new();
+        let mut kept_offset = 0;
+        let mut starts = Vec::new();
+        for chunk in &chunks {
+            let mut buf = kept.clone();
+            buf.extend_from_slice(chunk);
+            let mut pos = 0;
+            while let Some(start) = knuth_morris_pratt(&buf[pos..], pattern) {
+                starts.push(kept_offset + pos + start);
+                pos += start + pattern.len();
+            }
+            for b in *chunk {
+                automaton.push(b);
+            }
+            let keep = automaton.partial_match_len();
+            assert!(keep < pattern.len());
+            kept = buf[buf.len() - keep..].to_vec();
+            kept_offset += buf.len() - keep;
+        }
+        assert_eq!(starts, [2, 7, 13]);
+        assert_eq!(starts, knuth_morris_pratt_all(&text, pattern).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut automaton = Automaton::new(b"ab".to_vec());
+        assert_eq!(automaton.push(&b'a'), None);
+        automaton.reset();
+        assert_eq!(automaton.push(&b'b'), None);
+        assert_eq!(automaton.push(&b'a'), None);
+        assert_eq!(automaton.push(&b'b'), Some(3));
+    }
+}
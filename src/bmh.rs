@@ -0,0 +1,101 @@
+//! KMP search with a Boyer-Moore-Horspool skip table.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {feed, NextTable};
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// like `knuth_morris_pratt`, skipping ahead with a Boyer-Moore-Horspool
+/// table. Return the start of the substring as an offset from the start of
+/// the text inside a `Some`. If the pattern is not found, return `None`.
+///
+/// This is a hybrid of the two algorithms. When no part of the pattern is
+/// matched, the search looks at the element of the text under the last
+/// element of the pattern. If they differ, the search skips ahead by the
+/// distance from the last occurence of that element in the pattern to its
+/// end, or by the whole pattern if it doesn't occur, using a `HashMap` of
+/// the elements of the pattern. Otherwise, it takes one KMP step.
+///
+/// A skip never passes a match, so the worst case is still **O(n)**
+/// and the best case, for elements that are rare in the pattern, is
+/// **O(n / m)**. It pays off for large alphabets, where there is no `memchr`
+/// for a rare first element, but hashing makes each step slower than plain
+/// KMP.
+///
+/// Requires the `std` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_bmh;
+///
+/// let text = (0..1000u64).collect::<Vec<_>>();
+/// assert_eq!(knuth_morris_pratt_bmh(&text, &[500, 501, 502]), Some(500));
+/// ```
+pub fn knuth_morris_pratt_bmh<T>(text: &[T], pattern: &[T]) -> Option<usize>
+    where T: Eq + Hash
+{
+    if pattern.is_empty() {
+        return Some(0);
+    } else if pattern.len() > text.len() {
+        return None;
+    }
+
+    let m = pattern.len();
+    // the skip from each element to the end of the pattern, by its last
+    // occurence before the last element
+    let mut skips = HashMap::with_capacity(m - 1);
+    for (index, elem) in pattern[..m - 1].iter().enumerate() {
+        skips.insert(elem, m - 1 - index);
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut i = 0;
+    let mut j = 0;
+    while j < text.len() {
+        if i == 0 {
+            // only a match starting at `j` or later is possible
+            let last = text.get(j + m - 1)?;
+            if *last != pattern[m - 1] {
+                j += skips.get(last).cloned().unwrap_or(m);
+                continue;
+            }
+        }
+        if feed(&text[j], pattern, &next, &mut i, &mut equal) {
+            return Some(j + 1 - m);
+        }
+        j += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use knuth_morris_pratt;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_bmh() {
+        let texts = ["", "a", "substrinstring", "xxxxaxxaaxb", "abababcabababab", "abcbcbcba"];
+        let patterns = ["", "a", "string", "aab", "aaxb", "ababc", "abababab", "bcb", "cba"];
+        for text in &texts {
+            for pattern in &patterns {
+                assert_eq!(knuth_morris_pratt_bmh(text.as_bytes(), pattern.as_bytes()),
+                           knuth_morris_pratt(text.as_bytes(), pattern.as_bytes()),
+                           "text={:?} pattern={:?}", text, pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bmh_large_alphabet() {
+        let text = (0..10_000u64).map(|x| x * x % 7919).collect::<Vec<_>>();
+        for start in &[0, 1, 17, 5000, 9990] {
+            let pattern = &text[*start..*start + 10];
+            assert_eq!(knuth_morris_pratt_bmh(&text, pattern),
+                       knuth_morris_pratt(&text, pattern));
+        }
+        assert_eq!(knuth_morris_pratt_bmh(&text, &[7919, 1]), None);
+    }
+}
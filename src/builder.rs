@@ -0,0 +1,154 @@
+//! A builder to configure a search.
+
+use matches::MatchState;
+
+/// A builder for a search of a pattern in a text, with options for the
+/// kind of matches to find.
+///
+/// The options are set with the methods `overlapping`, `from`, `reverse`
+/// and `by`, and the search is run with `first`, `all` or `count`.
+///
+/// ```
+/// use knuth_morris_pratt::SearchBuilder;
+///
+/// let text = b"aaaa";
+/// assert_eq!(SearchBuilder::new(text, b"aa").count(), 2);
+/// assert_eq!(SearchBuilder::new(text, b"aa").overlapping(true).count(), 3);
+/// assert_eq!(SearchBuilder::new(text, b"aa").from(1).first(), Some(1));
+/// assert_eq!(SearchBuilder::new(text, b"AA")
+///                .by(|a, b| a.eq_ignore_ascii_case(b))
+///                .reverse(true)
+///                .all()
+///                .collect::<Vec<_>>(),
+///            [2, 0]);
+/// ```
+pub struct SearchBuilder<'a, T: 'a, FEq = fn(&T, &T) -> bool> {
+    text: &'a [T],
+    pattern: &'a [T],
+    equal: FEq,
+    overlapping: bool,
+    from: usize,
+    reverse: bool,
+}
+
+impl<'a, T, FEq> Clone for SearchBuilder<'a, T, FEq>
+    where FEq: Clone
+{
+    fn clone(&self) -> Self {
+        SearchBuilder {
+            equal: self.equal.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<'a, T> SearchBuilder<'a, T>
+    where T: PartialEq
+{
+    /// Create a builder for a search of `pattern` as a substring of `text`.
+    ///
+    /// By default, the search finds non-overlapping matches in the whole
+    /// text, front to back, using `PartialEq` for equality comparison.
+    pub fn new(text: &'a [T], pattern: &'a [T]) -> Self {
+        SearchBuilder {
+            text,
+            pattern,
+            equal: PartialEq::eq,
+            overlapping: false,
+            from: 0,
+            reverse: false,
+        }
+    }
+}
+
+impl<'a, T, FEq> SearchBuilder<'a, T, FEq> {
+    /// Whether to include overlapping matches. The default is `false`.
+    pub fn overlapping(mut self, overlapping: bool) -> Self {
+        self.overlapping = overlapping;
+        self
+    }
+
+    /// Only search the text from offset `from`. The matches are still
+    /// reported as offsets from the start of the text. If `from` is past the
+    /// end of the text, there are no matches. The default is `0`.
+    pub fn from(mut self, from: usize) -> Self {
+        self.from = from;
+        self
+    }
+
+    /// Whether to search from the end of the text, like `str::rmatch_indices`.
+    /// The matches are reported last to first. The default is `false`.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Use the function `equal` for equality comparison. It is called with
+    /// an element of the pattern as the first argument and an element of the
+    /// text as the second.
+    pub fn by<G>(self, equal: G) -> SearchBuilder<'a, T, G>
+        where G: FnMut(&T, &T) -> bool
+    {
+        SearchBuilder {
+            text: self.text,
+            pattern: self.pattern,
+            equal,
+            overlapping: self.overlapping,
+            from: self.from,
+            reverse: self.reverse,
+        }
+    }
+}
+
+impl<'a, T, FEq> SearchBuilder<'a, T, FEq>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    /// Return the first match, if any.
+    pub fn first(self) -> Option<usize> {
+        self.all().next()
+    }
+
+    /// Return an iterator of all the matches.
+    pub fn all(mut self) -> SearchMatches<'a, T, FEq> {
+        let state = self.text.get(self.from..).map(|text| {
+            if self.reverse {
+                MatchState::new_rev_by(text, self.pattern, &mut self.equal)
+            } else {
+                MatchState::new_by(text, self.pattern, &mut self.equal)
+            }
+        });
+        SearchMatches {
+            state,
+            equal: self.equal,
+            overlapping: self.overlapping,
+            offset: self.from,
+        }
+    }
+
+    /// Count the matches.
+    pub fn count(self) -> usize {
+        self.all().count()
+    }
+}
+
+/// An iterator of the start offsets of the matches of a search.
+///
+/// Created with the method `SearchBuilder::all`.
+pub struct SearchMatches<'a, T: 'a, FEq = fn(&T, &T) -> bool> {
+    // None if the search starts past the end of the text
+    state: Option<MatchState<'a, 'a, T>>,
+    equal: FEq,
+    overlapping: bool,
+    offset: usize,
+}
+
+impl<'a, T, FEq> Iterator for SearchMatches<'a, T, FEq>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        let state = self.state.as_mut()?;
+        state.next_match(self.overlapping, &mut self.equal)
+             .map(|start| start + self.offset)
+    }
+}
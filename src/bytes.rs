@@ -0,0 +1,137 @@
+//! Byte search accelerated with memchr.
+
+use memchr::{memchr, memchr2};
+
+use {feed, NextTable};
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// like `knuth_morris_pratt`, for bytes. Return the start of the substring
+/// as an offset from the start of the text inside a `Some`. If the pattern
+/// is not found, return `None`.
+///
+/// When no part of the pattern is matched, the search skips ahead to the
+/// next occurence of the first byte of the pattern using `memchr`, which is
+/// much faster if that byte is rare in the text.
+///
+/// Requires the `memchr` feature.
+pub fn knuth_morris_pratt_bytes(text: &[u8], pattern: &[u8]) -> Option<usize> {
+    if pattern.is_empty() {
+        return Some(0);
+    } else if pattern.len() > text.len() {
+        return None;
+    } else if pattern.len() == 1 {
+        return memchr(pattern[0], text);
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut i = 0;
+    let mut j = 0;
+    while j < text.len() {
+        if i == 0 {
+            j += memchr(pattern[0], &text[j..])?;
+        }
+        if feed(&text[j], pattern, &next, &mut i, &mut equal) {
+            return Some(j + 1 - pattern.len());
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// ignoring ASCII case, like `knuth_morris_pratt_ascii_case_insensitive`.
+/// Return the start of the substring as an offset from the start of the
+/// text inside a `Some`. If the pattern is not found, return `None`.
+///
+/// When no part of the pattern is matched, the search skips ahead to the
+/// next occurence of either case of the first byte of the pattern using
+/// `memchr2`.
+///
+/// Requires the `memchr` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_bytes_ascii_ci;
+///
+/// assert_eq!(knuth_morris_pratt_bytes_ascii_ci(b"Hello, World", b"WORLD"), Some(7));
+/// ```
+pub fn knuth_morris_pratt_bytes_ascii_ci(text: &[u8], pattern: &[u8]) -> Option<usize> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    let lower = pattern[0].to_ascii_lowercase();
+    let upper = pattern[0].to_ascii_uppercase();
+    if pattern.len() > text.len() {
+        return None;
+    } else if pattern.len() == 1 {
+        return memchr2(lower, upper, text);
+    }
+
+    let mut equal = u8::eq_ignore_ascii_case;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut i = 0;
+    let mut j = 0;
+    while j < text.len() {
+        if i == 0 {
+            j += memchr2(lower, upper, &text[j..])?;
+        }
+        if feed(&text[j], pattern, &next, &mut i, &mut equal) {
+            return Some(j + 1 - pattern.len());
+        }
+        j += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {knuth_morris_pratt, knuth_morris_pratt_ascii_case_insensitive};
+
+    #[test]
+    fn test_bytes() {
+        let texts = ["", "a", "substrinstring", "xxxxaxxaaxb", "abababcabababab"];
+        let patterns = ["", "a", "string", "aab", "aaxb", "ababc", "abababab", "xyzz"];
+        for text in &texts {
+            for pattern in &patterns {
+                assert_eq!(knuth_morris_pratt_bytes(text.as_bytes(), pattern.as_bytes()),
+                           knuth_morris_pratt(text.as_bytes(), pattern.as_bytes()),
+                           "text={:?} pattern={:?}", text, pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes_ascii_ci() {
+        let texts = ["", "A", "subSTRINstring", "xXxXAxxaAxB", "AbAbABcabababab", "1@[`{"];
+        let patterns = ["", "a", "String", "aab", "AAXB", "abaBc", "abababab", "xyzz", "@`", "[{"];
+        for text in &texts {
+            for pattern in &patterns {
+                assert_eq!(knuth_morris_pratt_bytes_ascii_ci(text.as_bytes(), pattern.as_bytes()),
+                           knuth_morris_pratt_ascii_case_insensitive(text.as_bytes(),
+                                                                     pattern.as_bytes()),
+                           "text={:?} pattern={:?}", text, pattern);
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_searcher_find_iter_bytes() {
+        use alloc::vec::Vec;
+        use {knuth_morris_pratt_all, Searcher};
+
+        let texts = ["", "a", "substrinstring", "xxxxaxxaaxb", "abababcabababab", "aaaaa"];
+        let patterns = ["", "a", "aa", "string", "aab", "aaxb", "ababc", "abab", "xyzz"];
+        for pattern in &patterns {
+            let searcher = Searcher::new(pattern.as_bytes().to_vec());
+            for text in &texts {
+                assert_eq!(searcher.find_iter_bytes(text.as_bytes()).collect::<Vec<_>>(),
+                           knuth_morris_pratt_all(text.as_bytes(), pattern.as_bytes())
+                               .collect::<Vec<_>>(),
+                           "text={:?} pattern={:?}", text, pattern);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Search in decoded text, by `char` or by grapheme cluster.
+
+#[cfg(feature = "unicode")]
+use alloc::vec::Vec;
+#[cfg(feature = "unicode")]
+use unicode_segmentation::UnicodeSegmentation;
+
+#[cfg(feature = "unicode")]
+use knuth_morris_pratt_iter;
+use knuth_morris_pratt;
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any. Return the start of the substring as an offset in `char`s from
+/// the start of the text inside a `Some`. If the pattern is not found,
+/// return `None`.
+///
+/// This compares `char`s, so a combining mark is a separate element: `e`
+/// matches the start of `e\u{301}` (`é` in decomposed form). See
+/// `knuth_morris_pratt_graphemes`.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_chars;
+///
+/// let text = "a→b→c".chars().collect::<Vec<_>>();
+/// assert_eq!(knuth_morris_pratt_chars(&text, &['→', 'c']), Some(3));
+/// ```
+pub fn knuth_morris_pratt_chars(text: &[char], pattern: &[char]) -> Option<usize> {
+    knuth_morris_pratt(text, pattern)
+}
+
+/// Search for the first occurence of `pattern` in `text`, comparing
+/// extended grapheme clusters, if any. Return the start of the match as an
+/// offset in grapheme clusters from the start of the text inside a `Some`.
+/// If the pattern is not found, return `None`.
+///
+/// The offset counts clusters, not `char`s or bytes. A match always starts
+/// and ends at cluster boundaries, so `e` does not match in `e\u{301}`, but
+/// `e\u{301}` does, as one cluster. The clusters are compared by their
+/// `char`s, without normalization: `\u{e9}` does not match `e\u{301}`.
+///
+/// Requires the `unicode` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_graphemes;
+///
+/// assert_eq!(knuth_morris_pratt_graphemes("cafe\u{301}!", "e\u{301}"), Some(3));
+/// assert_eq!(knuth_morris_pratt_graphemes("cafe\u{301}!", "e"), None);
+/// ```
+#[cfg(feature = "unicode")]
+pub fn knuth_morris_pratt_graphemes(text: &str, pattern: &str) -> Option<usize> {
+    let pattern = pattern.graphemes(true).collect::<Vec<_>>();
+    knuth_morris_pratt_iter(text.graphemes(true), &pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_chars() {
+        let texts = ["", "a", "substrinstring", "aé→éb", "e\u{301}e"];
+        let patterns = ["", "a", "string", "→é", "é", "e"];
+        for text in &texts {
+            for pattern in &patterns {
+                let truth = text.find(pattern).map(|start| text[..start].chars().count());
+                assert_eq!(knuth_morris_pratt_chars(&chars(text), &chars(pattern)), truth);
+            }
+        }
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_graphemes() {
+        let text = "cafe\u{301} nai\u{308}f";
+        // the chars match inside of a cluster
+        assert_eq!(knuth_morris_pratt_chars(&chars(text), &chars("e")), Some(3));
+        assert_eq!(knuth_morris_pratt_graphemes(text, "e"), None);
+        assert_eq!(knuth_morris_pratt_graphemes(text, "i"), None);
+        assert_eq!(knuth_morris_pratt_graphemes(text, "e\u{301}"), Some(3));
+        assert_eq!(knuth_morris_pratt_graphemes(text, "e\u{301} n"), Some(3));
+        assert_eq!(knuth_morris_pratt_graphemes(text, "i\u{308}f"), Some(7));
+        assert_eq!(knuth_morris_pratt_graphemes(text, "caf"), Some(0));
+        assert_eq!(knuth_morris_pratt_graphemes(text, "cafe"), None);
+        assert_eq!(knuth_morris_pratt_graphemes(text, "caf\u{e9}"), None);
+        assert_eq!(knuth_morris_pratt_graphemes(text, ""), Some(0));
+        assert_eq!(knuth_morris_pratt_graphemes("", "a"), None);
+    }
+}
@@ -0,0 +1,64 @@
+//! The error type of the fallible searches.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+/// The error of a fallible search.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum KmpError {
+    /// The scratch buffer is too short for the shift table of the pattern.
+    ScratchTooSmall {
+        /// The length the shift table needs, `pattern.len() + 1`.
+        needed: usize,
+        /// The length of the scratch buffer.
+        got: usize,
+    },
+    /// The pattern is too long for a shift table in this build: without the
+    /// `alloc` feature, the table must fit on the stack.
+    PatternTooLong {
+        /// The length of the pattern.
+        len: usize,
+        /// The length of the longest pattern that can be searched for.
+        max: usize,
+    },
+    /// Reading the text, or writing the output, failed.
+    ///
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+}
+
+impl fmt::Display for KmpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KmpError::ScratchTooSmall { needed, got } => {
+                write!(f, "scratch of length {} is too short for the shift table, it needs {}",
+                       got, needed)
+            }
+            KmpError::PatternTooLong { len, max } => {
+                write!(f, "pattern of length {} is too long, the maximum is {}", len, max)
+            }
+            #[cfg(feature = "std")]
+            KmpError::Io(ref e) => write!(f, "i/o error: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KmpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            KmpError::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for KmpError {
+    fn from(error: io::Error) -> Self {
+        KmpError::Io(error)
+    }
+}
@@ -0,0 +1,149 @@
+//! Search in containers other than slices.
+
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::slice;
+
+use knuth_morris_pratt_iter;
+
+/// A text that can be searched with `knuth_morris_pratt_haystack`.
+///
+/// The search only needs to visit the elements of the text once, front to
+/// back, so any container that can iterate over references to its elements
+/// can implement it, for example a rope or a tree of chunks.
+///
+/// It is implemented for slices, `Vec` and `VecDeque`.
+///
+/// Only `knuth_morris_pratt_haystack` searches a `Haystack`; the other
+/// search functions, like `knuth_morris_pratt`, take slices. They share one
+/// scan with the reverse searches and the searches that resume at an
+/// offset, which need random access to the text, and a sequential trait
+/// would need a second code path for them.
+pub trait Haystack<'a, T: 'a> {
+    /// The iterator of the elements of the text.
+    type Elements: Iterator<Item = &'a T>;
+
+    /// Return an iterator of the elements of the text, in order.
+    fn elements(self) -> Self::Elements;
+
+    /// Return the number of elements in the text, if it is known without
+    /// iterating. The search uses it to skip texts that are shorter than the
+    /// pattern.
+    fn len_hint(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<'a, T: 'a> Haystack<'a, T> for &'a [T] {
+    type Elements = slice::Iter<'a, T>;
+    fn elements(self) -> Self::Elements {
+        self.iter()
+    }
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: 'a> Haystack<'a, T> for &'a Vec<T> {
+    type Elements = slice::Iter<'a, T>;
+    fn elements(self) -> Self::Elements {
+        self.iter()
+    }
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: 'a> Haystack<'a, T> for &'a VecDeque<T> {
+    type Elements = ::alloc::collections::vec_deque::Iter<'a, T>;
+    fn elements(self) -> Self::Elements {
+        self.iter()
+    }
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+/// Search for the first occurence of `pattern` in the text `haystack`, if
+/// any. Return the start of the match as the number of elements before it
+/// inside a `Some`. If the pattern is not found, return `None`.
+///
+/// The text is only iterated once, up to the end of the first match, using
+/// the same scan as `knuth_morris_pratt_iter`.
+///
+/// ```
+/// use std::iter::FlatMap;
+/// use std::slice;
+/// use knuth_morris_pratt::{knuth_morris_pratt_haystack, Haystack};
+///
+/// // a text stored in pieces
+/// struct Chunks<'a>(&'a [&'a [u8]]);
+///
+/// impl<'a> Haystack<'a, u8> for Chunks<'a> {
+///     type Elements = FlatMap<slice::Iter<'a, &'a [u8]>, &'a [u8], fn(&&'a [u8]) -> &'a [u8]>;
+///     fn elements(self) -> Self::Elements {
+///         self.0.iter().flat_map(|chunk| *chunk)
+///     }
+/// }
+///
+/// let text = Chunks(&[b"subs", b"trin", b"string"]);
+/// assert_eq!(knuth_morris_pratt_haystack(text, b"string"), Some(8));
+/// ```
+pub fn knuth_morris_pratt_haystack<'a, H, T>(haystack: H, pattern: &[T]) -> Option<usize>
+    where H: Haystack<'a, T>,
+          T: PartialEq + 'a
+{
+    if let Some(len) = haystack.len_hint() {
+        if pattern.len() > len {
+            return None;
+        }
+    }
+    knuth_morris_pratt_iter(haystack.elements(), pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::iter::FlatMap;
+
+    // a text stored in pieces
+    struct Chunks<'a>(&'a [&'a [u8]]);
+
+    impl<'a> Haystack<'a, u8> for Chunks<'a> {
+        type Elements = FlatMap<slice::Iter<'a, &'a [u8]>, slice::Iter<'a, u8>,
+                                fn(&'a &'a [u8]) -> slice::Iter<'a, u8>>;
+        fn elements(self) -> Self::Elements {
+            self.0.iter().flat_map(|chunk| chunk.iter())
+        }
+    }
+
+    #[test]
+    fn test_haystack() {
+        assert_eq!(knuth_morris_pratt_haystack(&b"substrinstring"[..], b"string"), Some(8));
+        assert_eq!(knuth_morris_pratt_haystack(&b"strin"[..], b"string"), None);
+
+        let chunks: [&[u8]; 4] = [b"subs", b"tri", b"", b"nstring"];
+        assert_eq!(knuth_morris_pratt_haystack(Chunks(&chunks), b"string"), Some(8));
+        assert_eq!(knuth_morris_pratt_haystack(Chunks(&chunks), b"strin"), Some(3));
+        assert_eq!(knuth_morris_pratt_haystack(Chunks(&chunks), b"strings"), None);
+        assert_eq!(knuth_morris_pratt_haystack(Chunks(&chunks), b""), Some(0));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_haystack_alloc() {
+        assert_eq!(knuth_morris_pratt_haystack(&vec![1, 2, 3, 4], &[3, 4]), Some(2));
+        assert_eq!(knuth_morris_pratt_haystack(&vec![1, 2, 3, 4], &[4, 3]), None);
+        assert_eq!(knuth_morris_pratt_haystack(&vec![1, 2], &[1, 2, 3]), None);
+
+        let mut deque = VecDeque::new();
+        deque.extend(&[3, 4]);
+        deque.push_front(2);
+        deque.push_front(1);
+        assert_eq!(knuth_morris_pratt_haystack(&deque, &[2, 3]), Some(1));
+    }
+}
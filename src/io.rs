@@ -0,0 +1,266 @@
+//! Search in a `std::io::Read`.
+
+use core::slice;
+use std::io::{self, ErrorKind, Read, Write};
+
+use {scan_end, KmpError, NextTable};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Search for the first occurence of `pattern` in the bytes read from
+/// `reader`, if any. Return the start of the match as a byte offset from
+/// the start of the stream inside a `Some`. If the pattern is not found
+/// before the end of the stream, return `None`.
+///
+/// The stream is read in fixed size chunks, and matches that span multiple
+/// chunks are found. Reading stops as soon as a match is found. If reading
+/// fails, return the error of the reader in `KmpError::Io`.
+///
+/// Requires the `std` feature.
+pub fn knuth_morris_pratt_reader<R>(mut reader: R, pattern: &[u8])
+    -> Result<Option<u64>, KmpError>
+    where R: Read
+{
+    if pattern.is_empty() {
+        return Ok(Some(0));
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut buf = [0; BUFFER_SIZE];
+    // the automaton state is kept across chunks
+    let mut i = 0;
+    // stream offset of the start of the current chunk
+    let mut offset = 0;
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(len) => len,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(KmpError::Io(e)),
+        };
+        let mut j = 0;
+        if scan_end(&buf[..len], pattern, &next, &mut i, &mut j, &mut equal) {
+            return Ok(Some(offset + j as u64 - pattern.len() as u64));
+        }
+        offset += len as u64;
+    }
+}
+
+/// Copy the bytes read from `reader` to `writer`, replacing all
+/// non-overlapping occurences of `pattern` with `replacement`, like
+/// `knuth_morris_pratt_replace` does in memory. Return the number of
+/// replacements.
+///
+/// The stream is read in fixed size chunks, and matches that span multiple
+/// chunks are replaced. The bytes at the end of a chunk that could start a
+/// match are held back until the next chunk decides it; they are always a
+/// prefix of the pattern, as long as `Automaton::partial_match_len`, so
+/// they need no buffer of their own. Like `str::replace`, the empty pattern
+/// matches at every offset, so the replacement is written before each byte
+/// and at the end.
+///
+/// If reading or writing fails, return the error in `KmpError::Io`. The
+/// writer is not flushed.
+///
+/// Requires the `std` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_replace_stream;
+///
+/// let mut out = Vec::new();
+/// let count = knuth_morris_pratt_replace_stream(&b"a cat, a hat"[..], &mut out, b"at", b"ow")
+///     .unwrap();
+/// assert_eq!(count, 2);
+/// assert_eq!(out, b"a cow, a how");
+/// ```
+pub fn knuth_morris_pratt_replace_stream<R, W>(mut reader: R, mut writer: W,
+                                               pattern: &[u8], replacement: &[u8])
+    -> Result<usize, KmpError>
+    where R: Read,
+          W: Write
+{
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut buf = [0; BUFFER_SIZE];
+    let mut count = 0;
+    if pattern.is_empty() {
+        writer.write_all(replacement)?;
+        count += 1;
+    }
+    // the first `i` bytes of the pattern are matched by the last bytes read,
+    // and not written yet
+    let mut i = 0;
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(KmpError::Io(e)),
+        };
+        let chunk = &buf[..len];
+        if pattern.is_empty() {
+            for byte in chunk {
+                writer.write_all(slice::from_ref(byte))?;
+                writer.write_all(replacement)?;
+            }
+            count += len;
+            continue;
+        }
+
+        // offsets into the bytes not written yet: the held back prefix of
+        // the pattern, then the chunk
+        let head = &pattern[..i];
+        let mut written = 0;
+        let mut j = 0;
+        while scan_end(chunk, pattern, &next, &mut i, &mut j, &mut equal) {
+            write_joined(&mut writer, head, chunk, written, head.len() + j - pattern.len())?;
+            writer.write_all(replacement)?;
+            written = head.len() + j;
+            count += 1;
+            // matches do not overlap
+            i = 0;
+        }
+        write_joined(&mut writer, head, chunk, written, head.len() + len - i)?;
+    }
+    writer.write_all(&pattern[..i])?;
+    Ok(count)
+}
+
+// Write the bytes `from..to` of `head` followed by `tail`
+fn write_joined<W>(writer: &mut W, head: &[u8], tail: &[u8], from: usize, to: usize)
+    -> io::Result<()>
+    where W: Write
+{
+    if from < head.len() {
+        writer.write_all(&head[from..to.min(head.len())])?;
+    }
+    if to > head.len() {
+        writer.write_all(&tail[from.saturating_sub(head.len())..to - head.len()])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use knuth_morris_pratt_replace;
+    use std::vec::Vec;
+
+    // a reader that returns at most `chunk` bytes from each read
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl<'a> Read for Chunked<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_reader() {
+        let text = b"substrinstring";
+        assert_eq!(knuth_morris_pratt_reader(&text[..], b"string").unwrap(), Some(8));
+        assert_eq!(knuth_morris_pratt_reader(&text[..], b"strung").unwrap(), None);
+        assert_eq!(knuth_morris_pratt_reader(&text[..], b"").unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_reader_split_pattern() {
+        // the pattern is split across the first and second read
+        let text = b"substrinstring";
+        for chunk in 1..text.len() + 1 {
+            let reader = Chunked { data: text, chunk };
+            assert_eq!(knuth_morris_pratt_reader(reader, b"string").unwrap(), Some(8));
+        }
+
+        let mut text = vec![b'a'; BUFFER_SIZE + 10];
+        text[BUFFER_SIZE - 2..BUFFER_SIZE + 2].copy_from_slice(b"abcd");
+        let found = knuth_morris_pratt_reader(&text[..], b"abcd").unwrap();
+        assert_eq!(found, Some(BUFFER_SIZE as u64 - 2));
+    }
+
+    #[test]
+    fn test_reader_error() {
+        struct Failing;
+
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(ErrorKind::BrokenPipe, "failing"))
+            }
+        }
+
+        match knuth_morris_pratt_reader(Failing, b"string") {
+            Err(KmpError::Io(ref e)) if e.kind() == ErrorKind::BrokenPipe => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    fn replace_stream<R: Read>(reader: R, pattern: &[u8], replacement: &[u8])
+        -> (Vec<u8>, usize)
+    {
+        let mut out = Vec::new();
+        let count = knuth_morris_pratt_replace_stream(reader, &mut out, pattern, replacement)
+            .unwrap();
+        (out, count)
+    }
+
+    #[test]
+    fn test_replace_stream() {
+        let texts = ["", "a", "aaaa", "substrinstring", "abababcabababab", "abcab"];
+        let patterns = ["", "a", "aa", "string", "abab", "ababc", "abcab"];
+        for text in &texts {
+            for pattern in &patterns {
+                let (t, p) = (text.as_bytes(), pattern.as_bytes());
+                let truth = knuth_morris_pratt_replace(t, p, b"<>");
+                let truth_count = text.matches(pattern).count();
+                for chunk in 1..t.len() + 2 {
+                    let reader = Chunked { data: t, chunk };
+                    assert_eq!(replace_stream(reader, p, b"<>"), (truth.clone(), truth_count),
+                               "text={:?} pattern={:?} chunk={}", text, pattern, chunk);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_replace_stream_split_pattern() {
+        // the pattern straddles the boundary of the internal buffer, after a
+        // false start that is held back across the previous boundary
+        let mut text = vec![b'x'; 2 * BUFFER_SIZE + 10];
+        text[BUFFER_SIZE - 3..BUFFER_SIZE + 1].copy_from_slice(b"abcd");
+        text[2 * BUFFER_SIZE - 2..2 * BUFFER_SIZE + 4].copy_from_slice(b"ababcd");
+        let (out, count) = replace_stream(&text[..], b"abcd", b"-");
+        assert_eq!(count, 2);
+        assert_eq!(out, knuth_morris_pratt_replace(&text, b"abcd", b"-"));
+
+        let (out, count) = replace_stream(&text[..BUFFER_SIZE - 1], b"abcd", b"-");
+        assert_eq!(count, 0);
+        assert_eq!(out, &text[..BUFFER_SIZE - 1]);
+    }
+
+    #[test]
+    fn test_replace_stream_error() {
+        struct Failing;
+
+        impl Write for Failing {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(ErrorKind::BrokenPipe, "failing"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        match knuth_morris_pratt_replace_stream(&b"abc"[..], Failing, b"b", b"x") {
+            Err(KmpError::Io(ref e)) if e.kind() == ErrorKind::BrokenPipe => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
@@ -5,26 +5,244 @@
 //! without cases that are spectacularly faster than the average.
 //!
 //! Substring search for a *pattern* of length m occuring in a *text* of length
-//! n computes in **O(n)** time and uses **O(min(n, m))** space.
+//! n computes in **O(n)** time and uses **O(min(n, m))** space. The
+//! exception is iterating the matches of `knuth_morris_pratt_all` from the
+//! back without the `alloc` feature, which can take **O(n²)** time for a
+//! pattern that overlaps itself; see `Matches`.
+//!
+//! The pattern must be shorter than `usize::MAX` elements (only possible to
+//! violate with zero-sized elements); the search panics for longer patterns.
+//! The fallible functions, like `knuth_morris_pratt_try`, return a
+//! `KmpError` instead of panicking.
+//!
+//! Empty texts and patterns behave like they do in `str` search, in every
+//! function of the crate:
+//!
+//! - The empty pattern matches at every offset from `0` to `text.len()`,
+//!   inclusive, so in the empty text it matches once, at `0`. The first
+//!   match is at `0` and the last at `text.len()`.
+//! - A nonempty pattern never matches in the empty text, nor in any text
+//!   shorter than itself.
+//! - Splitting by a pattern always yields one more piece than there are
+//!   matches, so splitting the empty text yields one empty piece.
+//!
+//! Floating point numbers are only `PartialEq`, so they can be searched
+//! with the functions that require only that, like `knuth_morris_pratt_rev`,
+//! or with `knuth_morris_pratt_by`. With `==`, a `NaN` is not equal to
+//! anything, not even itself, so a pattern that contains a `NaN` never
+//! matches, and `0.0` matches `-0.0`. `knuth_morris_pratt_total_f64`
+//! compares the numbers like `f64::total_cmp` instead, so that a `NaN`
+//! matches a bit-identical `NaN`.
+//!
+//! The crate is `no_std`. The feature `alloc` enables the `Searcher` and
+//! searching for patterns of any length; without it, patterns must be shorter
+//! than 32 elements. The feature `std` enables searching in a `std::io::Read`
+//! and the skip table search `knuth_morris_pratt_bmh`, and implies `alloc`.
+//! Both are enabled by default. Without an allocator, the `StackSearcher`
+//! keeps the shift table of a pattern of any length below its capacity in
+//! an array.
+//!
+//! Without default features, the crate also builds for targets like
+//! `wasm32-unknown-unknown`; the example `wasm` wraps the search in a C ABI
+//! for calling it from JavaScript.
+//!
+//! The optional feature `serde` implements serialization for `PrefixTable`
+//! and `Searcher`, and the optional feature `rayon` enables searching
+//! many texts in parallel. The optional feature `memchr` enables the faster
+//! byte searches `knuth_morris_pratt_bytes` and
+//! `knuth_morris_pratt_bytes_ascii_ci`. With the optional feature
+//! `smallvec`, the shift table of patterns shorter than 64 elements is kept
+//! on the stack instead of only for patterns shorter than 32 elements. The
+//! optional feature `unicode` enables searching by grapheme cluster with
+//! `knuth_morris_pratt_graphemes`. The feature `testing` exports
+//! `naive_search`, a simple reference implementation.
+
+#![no_std]
+#![cfg_attr(feature = "pattern", feature(pattern))]
+
+#[cfg(any(test, feature = "alloc"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "memchr")]
+extern crate memchr;
+#[cfg(feature = "smallvec")]
+extern crate smallvec;
+#[cfg(feature = "unicode")]
+extern crate unicode_segmentation;
+
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(any(test, feature = "alloc"))]
+use alloc::vec::Vec;
+#[cfg(all(feature = "smallvec", feature = "alloc"))]
+use smallvec::SmallVec;
+use core::borrow::Borrow;
+use core::mem;
+use core::ops::{Deref, DerefMut, Range};
+use core::slice;
+
+mod builder;
+mod chars;
+mod error;
+mod haystack;
+#[cfg(feature = "memchr")]
+mod bytes;
+mod matches;
+mod pattern;
+mod stack_searcher;
+mod stats;
+#[cfg(feature = "alloc")]
+mod searcher;
+#[cfg(feature = "alloc")]
+mod automaton;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod bmh;
+#[cfg(all(feature = "rayon", feature = "alloc"))]
+mod par;
+#[cfg(all(feature = "serde", feature = "alloc"))]
+mod serde_impl;
+
+pub use builder::{SearchBuilder, SearchMatches};
+pub use chars::knuth_morris_pratt_chars;
+#[cfg(feature = "unicode")]
+pub use chars::knuth_morris_pratt_graphemes;
+pub use error::KmpError;
+pub use haystack::{knuth_morris_pratt_haystack, Haystack};
+#[cfg(feature = "memchr")]
+pub use bytes::{knuth_morris_pratt_bytes, knuth_morris_pratt_bytes_ascii_ci};
+pub use matches::{
+    knuth_morris_pratt_all,
+    knuth_morris_pratt_all_by,
+    knuth_morris_pratt_bounds,
+    knuth_morris_pratt_count,
+    knuth_morris_pratt_count_by,
+    knuth_morris_pratt_count_overlapping,
+    knuth_morris_pratt_find_map,
+    knuth_morris_pratt_for_each,
+    knuth_morris_pratt_last,
+    knuth_morris_pratt_match_indices,
+    knuth_morris_pratt_match_ranges,
+    knuth_morris_pratt_matches_in,
+    knuth_morris_pratt_nth,
+    knuth_morris_pratt_overlapping,
+    knuth_morris_pratt_overlapping_from,
+    knuth_morris_pratt_overlapping_matches_from,
+    knuth_morris_pratt_overlapping_rmatches,
+    knuth_morris_pratt_rmatches,
+    knuth_morris_pratt_rsplit,
+    knuth_morris_pratt_split,
+    knuth_morris_pratt_trace,
+    knuth_morris_pratt_windows_matching,
+    CountedMatches,
+    MatchIndices,
+    MatchRanges,
+    Matches,
+    MatchesBy,
+    MatchesIn,
+    OverlapMatches,
+    OverlapRMatches,
+    RMatches,
+    RSplit,
+    Split,
+    Trace,
+    WindowsMatching,
+};
+#[cfg(all(feature = "memchr", feature = "alloc"))]
+pub use searcher::BytesMatches;
+#[cfg(feature = "alloc")]
+pub use matches::{
+    knuth_morris_pratt_all_into,
+    knuth_morris_pratt_all_robust,
+    knuth_morris_pratt_histogram,
+    knuth_morris_pratt_tally,
+};
+pub use pattern::{knuth_morris_pratt_str, KmpPattern, KmpSearcher};
+pub use stack_searcher::StackSearcher;
+pub use stats::{knuth_morris_pratt_instrumented, SearchStats};
+#[cfg(feature = "alloc")]
+pub use searcher::{
+    knuth_morris_pratt_tally_searchers,
+    OwnedMatches,
+    Searcher,
+    SearcherMatches,
+};
+#[cfg(feature = "alloc")]
+pub use automaton::Automaton;
+#[cfg(feature = "std")]
+pub use io::{knuth_morris_pratt_reader, knuth_morris_pratt_replace_stream};
+#[cfg(feature = "std")]
+pub use bmh::knuth_morris_pratt_bmh;
+#[cfg(all(feature = "rayon", feature = "alloc"))]
+pub use par::knuth_morris_pratt_par_contains;
+
+// Random access to a sequence of elements; lets the algorithm run over
+// slices both forward and backward.
+trait Seq: Copy {
+    type Item;
+    fn len(self) -> usize;
+    fn at(&self, i: usize) -> &Self::Item;
+}
+
+impl<T> Seq for &[T] {
+    type Item = T;
+    #[inline]
+    fn len(self) -> usize { <[T]>::len(self) }
+    #[inline]
+    fn at(&self, i: usize) -> &T { &self[i] }
+}
+
+// A slice viewed back to front
+struct Rev<'a, T: 'a>(&'a [T]);
+
+impl<'a, T> Clone for Rev<'a, T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, T> Copy for Rev<'a, T> { }
+
+impl<'a, T> Seq for Rev<'a, T> {
+    type Item = T;
+    #[inline]
+    fn len(self) -> usize { self.0.len() }
+    #[inline]
+    fn at(&self, i: usize) -> &T { &self.0[self.0.len() - 1 - i] }
+}
 
 // compute a KMP shift table for each element of the pattern `x`.
 // !0 is a sentinel value.
-fn prepare_kmp<T, FEq>(x: &[T], next: &mut [usize], equal: &mut FEq)
-    where FEq: FnMut(&T, &T) -> bool
+//
+// The table has an entry for each position of the pattern and one for the
+// end of it, so `next` must have room for `x.len() + 1` entries. This also
+// means that `x.len() < usize::MAX`, so the sentinel is never a position in
+// the pattern. Only slices of zero-sized elements can be that long.
+fn prepare_kmp<S, FEq>(x: S, next: &mut [usize], equal: &mut FEq)
+    where S: Seq,
+          FEq: FnMut(&S::Item, &S::Item) -> bool
 {
+    debug_assert!(next.len() > x.len(),
+                  "prepare_kmp: table of length {} for pattern of length {}",
+                  next.len(), x.len());
     let mut i = 0;
     let mut j = !0;
     next[0] = !0;
     while i < x.len() {
         while let Some(&next_j) = next.get(j) { // .get(!0) -> None
-            if equal(&x[i], &x[j]) {
+            if equal(x.at(i), x.at(j)) {
                 break;
             }
             j = next_j;
         }
         i += 1;
         j = j.wrapping_add(1);
-        if i != x.len() && equal(&x[i], &x[j]) {
+        if i != x.len() && equal(x.at(i), x.at(j)) {
             next[i] = next[j];
         } else {
             next[i] = j;
@@ -34,6 +252,180 @@ fn prepare_kmp<T, FEq>(x: &[T], next: &mut [usize], equal: &mut FEq)
 
 const STACK_NEXT_SIZE: usize = 32;
 
+// storage for the KMP shift table; use the stack for short patterns
+#[allow(clippy::large_enum_variant)] // avoiding the allocation is the point
+#[derive(Clone)]
+enum NextTable {
+    Stack([usize; STACK_NEXT_SIZE]),
+    #[cfg(all(feature = "alloc", not(feature = "smallvec")))]
+    Heap(Vec<usize>),
+    // with the smallvec feature, medium sized tables stay on the stack too
+    #[cfg(all(feature = "smallvec", feature = "alloc"))]
+    Small(SmallVec<[usize; SMALL_NEXT_SIZE]>),
+}
+
+#[cfg(all(feature = "smallvec", feature = "alloc"))]
+const SMALL_NEXT_SIZE: usize = 64;
+
+impl NextTable {
+    // compute the shift table for `pattern`
+    fn new<S, FEq>(pattern: S, equal: &mut FEq) -> Self
+        where S: Seq,
+              FEq: FnMut(&S::Item, &S::Item) -> bool
+    {
+        let mut table = NextTable::for_len(pattern.len());
+        prepare_kmp(pattern, &mut table, equal);
+        table
+    }
+
+    // a zeroed table with room for a pattern of length `len`
+    fn for_len(len: usize) -> Self {
+        // the stack table fits patterns up to length STACK_NEXT_SIZE - 1
+        if len >= STACK_NEXT_SIZE {
+            let len = len.checked_add(1)
+                         .expect("knuth_morris_pratt: pattern too long");
+            NextTable::heap(len)
+        } else {
+            NextTable::Stack([0; STACK_NEXT_SIZE])
+        }
+    }
+
+    #[cfg(all(feature = "alloc", not(feature = "smallvec")))]
+    fn heap(len: usize) -> Self {
+        NextTable::Heap(vec![0; len])
+    }
+
+    #[cfg(all(feature = "alloc", feature = "smallvec"))]
+    fn heap(len: usize) -> Self {
+        NextTable::Small(SmallVec::from_elem(0, len))
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn heap(_len: usize) -> Self {
+        panic!("knuth_morris_pratt: patterns of {} or more elements require \
+                the alloc feature", STACK_NEXT_SIZE)
+    }
+}
+
+impl Deref for NextTable {
+    type Target = [usize];
+    fn deref(&self) -> &[usize] {
+        match *self {
+            NextTable::Stack(ref a) => a,
+            #[cfg(all(feature = "alloc", not(feature = "smallvec")))]
+            NextTable::Heap(ref v) => v,
+            #[cfg(all(feature = "smallvec", feature = "alloc"))]
+            NextTable::Small(ref v) => v,
+        }
+    }
+}
+
+impl DerefMut for NextTable {
+    fn deref_mut(&mut self) -> &mut [usize] {
+        match *self {
+            NextTable::Stack(ref mut a) => a,
+            #[cfg(all(feature = "alloc", not(feature = "smallvec")))]
+            NextTable::Heap(ref mut v) => v,
+            #[cfg(all(feature = "smallvec", feature = "alloc"))]
+            NextTable::Small(ref mut v) => v,
+        }
+    }
+}
+
+// Scan `text` from text position `*j` and pattern position `*i` using the
+// shift table `next`. Return the start of the next match, if any. `equal`
+// compares an element of the pattern with an element of the text, which can
+// be of another type.
+//
+// When a match is found, `*i == pattern.len()` and the caller must pick the
+// resume state for `*i` before scanning again.
+fn scan<S, U, FEq>(text: U, pattern: S, next: &[usize],
+                   i: &mut usize, j: &mut usize, equal: &mut FEq)
+    -> Option<usize>
+    where S: Seq,
+          U: Seq,
+          FEq: FnMut(&S::Item, &U::Item) -> bool
+{
+    if scan_end(text, pattern, next, i, j, equal) {
+        Some(*j - *i)
+    } else {
+        None
+    }
+}
+
+// Search for the first match of `pattern` using its shift table `next`.
+fn find_with_table<T, FEq>(text: &[T], pattern: &[T], next: &[usize], equal: &mut FEq)
+    -> Option<usize>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    // empty pattern is a trivial match
+    if pattern.is_empty() {
+        Some(0)
+    } else if pattern.len() > text.len() {
+        None
+    } else {
+        scan(text, pattern, next, &mut 0, &mut 0, equal)
+    }
+}
+
+// Like `scan`, but return true if a match was found, ending at `*j`. The
+// match may have started in text that was scanned before `text`.
+fn scan_end<S, U, FEq>(text: U, pattern: S, next: &[usize],
+                       i: &mut usize, j: &mut usize, equal: &mut FEq)
+    -> bool
+    where S: Seq,
+          U: Seq,
+          FEq: FnMut(&S::Item, &U::Item) -> bool
+{
+    while *j < text.len() {
+        while let Some(&next_i) = next.get(*i) { // .get(!0) -> None
+            if equal(pattern.at(*i), text.at(*j)) {
+                break;
+            }
+            *i = next_i;
+        }
+        *i = i.wrapping_add(1);
+        *j += 1;
+        if *i >= pattern.len() {
+            return true;
+        }
+    }
+    false
+}
+
+// Feed one element of the text to the automaton with pattern position `*i`.
+// Return true if it completes a match.
+fn feed<T, FEq>(elem: &T, pattern: &[T], next: &[usize], i: &mut usize,
+                equal: &mut FEq)
+    -> bool
+    where FEq: FnMut(&T, &T) -> bool
+{
+    scan_end(slice::from_ref(elem), pattern, next, i, &mut 0, equal)
+}
+
+// Return the length of the longest border of any prefix of `pattern`: the
+// longest proper prefix of a prefix that is also a suffix of it.
+#[cfg(feature = "alloc")]
+fn max_prefix_border<T, FEq>(pattern: &[T], equal: &mut FEq) -> usize
+    where FEq: FnMut(&T, &T) -> bool
+{
+    // the unoptimized failure function, by prefix length
+    let mut border = NextTable::for_len(pattern.len());
+    let mut k = 0;
+    let mut max = 0;
+    for i in 1..pattern.len() {
+        while k > 0 && !equal(&pattern[i], &pattern[k]) {
+            k = border[k];
+        }
+        if equal(&pattern[i], &pattern[k]) {
+            k += 1;
+        }
+        border[i + 1] = k;
+        max = max.max(k);
+    }
+    max
+}
+
 /// Search for the first occurence of `pattern` as a substring of `text`,
 /// if any. Return the start of the substring as an offset from the start of
 /// the text inside a `Some`. If the pattern is not found, return `None`.
@@ -43,84 +435,2365 @@ pub fn knuth_morris_pratt<T>(text: &[T], pattern: &[T]) -> Option<usize>
     knuth_morris_pratt_by(text, pattern, PartialEq::eq)
 }
 
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, like `knuth_morris_pratt`. Return the start of the substring as
+/// an offset from the start of the text inside a `Some`. If the pattern is
+/// not found, return `None`.
+///
+/// Return `KmpError::PatternTooLong` instead of panicking if the pattern is
+/// too long for a shift table in this build, whatever the text: without the
+/// `alloc` feature, patterns must be shorter than 32 elements. With it, the
+/// table must still fit in the address space, which only limits patterns of
+/// zero-sized elements.
+pub fn knuth_morris_pratt_try<T>(text: &[T], pattern: &[T]) -> Result<Option<usize>, KmpError>
+    where T: Eq
+{
+    // the longest pattern whose table of `len + 1` entries can be allocated
+    let max = if cfg!(feature = "alloc") {
+        isize::MAX as usize / mem::size_of::<usize>() - 1
+    } else {
+        STACK_NEXT_SIZE - 1
+    };
+    if pattern.len() > max {
+        return Err(KmpError::PatternTooLong { len: pattern.len(), max });
+    }
+    Ok(knuth_morris_pratt(text, pattern))
+}
+
 /// Search for the first occurence of `pattern` as a substring of `text`,
 /// if any. Return the start of the substring as an offset from the start of
 /// the text inside a `Some`. If the pattern is not found, return `None`.
 ///
 /// Use the function `equal` for equality comparison.
+///
+/// `equal` must be an equivalence relation (reflexive, symmetric and
+/// transitive), like `==` for an `Eq` type. The shift table relies on this, so
+/// with any other function the result is unspecified: matches can be missed
+/// or reported where there are none. See `knuth_morris_pratt_by_checked`.
 pub fn knuth_morris_pratt_by<T, FEq>(text: &[T], pattern: &[T], mut equal: FEq)
     -> Option<usize>
     where FEq: FnMut(&T, &T) -> bool
 {
     // empty pattern is a trivial match
-    if pattern.len() == 0 {
+    if pattern.is_empty() {
         return Some(0);
     } else if pattern.len() > text.len() {
         return None;
+    } else if pattern.len() == 1 {
+        // the shift table is trivial
+        return text.iter().position(|elem| equal(&pattern[0], elem));
     }
 
-    // use the stack for short patterns
-    let mut next_vec;
-    let mut next_stack = [0; STACK_NEXT_SIZE];
-    let next;
-    if pattern.len() >= STACK_NEXT_SIZE {
-        next_vec = vec![0; pattern.len() + 1];
-        next = &mut next_vec[..];
-    } else {
-        next = &mut next_stack[..];
-    }
-    prepare_kmp(pattern, next, &mut equal);
-    
-    let mut i = 0;
-    let mut j = 0;
-    while j < text.len() {
-        while let Some(&next_i) = next.get(i) { // .get(!0) -> None
-            if equal(&pattern[i], &text[j]) {
-                break;
-            }
-            i = next_i;
+    let next = NextTable::new(pattern, &mut equal);
+    scan(text, pattern, &next, &mut 0, &mut 0, &mut equal)
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, like `knuth_morris_pratt_by`.
+///
+/// In debug builds, check that `equal` behaves like an equivalence relation
+/// on the elements of the pattern first: each element must be equal to
+/// itself, and neighbouring elements must compare the same in both orders.
+/// In release builds, this is the same as `knuth_morris_pratt_by`.
+///
+/// ***Panics*** in debug builds if the check fails.
+pub fn knuth_morris_pratt_by_checked<T, FEq>(text: &[T], pattern: &[T], mut equal: FEq)
+    -> Option<usize>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    if cfg!(debug_assertions) {
+        for (index, elt) in pattern.iter().enumerate() {
+            assert!(equal(elt, elt),
+                    "knuth_morris_pratt_by_checked: equality is not reflexive: \
+                     pattern element {} is not equal to itself", index);
         }
-        i = i.wrapping_add(1);
-        j += 1;
-        if i >= pattern.len() {
-            return Some(j - i);
-            // i = next[i]; to continue searching after first match
+        for (index, pair) in pattern.windows(2).enumerate() {
+            assert!(equal(&pair[0], &pair[1]) == equal(&pair[1], &pair[0]),
+                    "knuth_morris_pratt_by_checked: equality is not symmetric \
+                     for pattern elements {} and {}", index, index + 1);
         }
     }
-    None
+    knuth_morris_pratt_by(text, pattern, equal)
 }
 
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, like `knuth_morris_pratt`, picking the algorithm by the pattern.
+/// Return the start of the substring as an offset from the start of the
+/// text inside a `Some`. If the pattern is not found, return `None`.
+///
+/// If the first element of the pattern does not occur again in it, a naive
+/// search that compares each offset of the text in turn is also linear,
+/// and does without the shift table, so it is used. Otherwise this is KMP.
+/// This is exactly the case of `Searcher::pathological_risk` being zero;
+/// callers that know more about their texts and comparisons can use that
+/// heuristic to pick an algorithm themselves.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_adaptive;
+///
+/// assert_eq!(knuth_morris_pratt_adaptive(b"substrinstring", b"string"), Some(8));
+/// assert_eq!(knuth_morris_pratt_adaptive(b"aaaaaaab", b"aab"), Some(5));
+/// ```
+pub fn knuth_morris_pratt_adaptive<T>(text: &[T], pattern: &[T]) -> Option<usize>
+    where T: PartialEq
+{
+    if pattern.is_empty() {
+        return Some(0);
+    } else if pattern.len() > text.len() {
+        return None;
+    } else if pattern[1..].contains(&pattern[0]) {
+        return knuth_morris_pratt_by(text, pattern, PartialEq::eq);
+    }
 
-// more thorough tests in the tests/ directory
-#[test]
-fn basic_test() {
-    let body = "G";
-    let pattern = "GCAGAGAG";
-    knuth_morris_pratt(body.as_bytes(), pattern.as_bytes());
-    macro_rules! test {
-        ($body:expr, $pattern:expr) => {
-            assert_eq!($body.find($pattern),
-                       knuth_morris_pratt($body.as_bytes(), $pattern.as_bytes()),
-                       "assertion failed for body={}, pattern={}",
-                       $body, $pattern)
-        }
+    // a partial match at one offset can't overlap a match at the next
+    // offsets, so each element of the text is compared about twice
+    (0..text.len() - pattern.len() + 1).find(|&start| {
+        pattern.iter().zip(&text[start..]).all(|(p, t)| p == t)
+    })
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// where the text has a different element type than the pattern. Return the
+/// start of the substring as an offset from the start of the text inside a
+/// `Some`. If the pattern is not found, return `None`.
+///
+/// Use the function `equal` to compare an element of the pattern with an
+/// element of the text, and `pattern_equal` to compare elements of the
+/// pattern with each other when computing the shift table. They must agree,
+/// and `pattern_equal` must be an equivalence relation:
+///
+/// - if `pattern_equal(a, b)`, then `equal(a, x) == equal(b, x)` for every
+///   element `x` of the text, and
+/// - if `equal(a, x)` and `equal(b, x)`, then `pattern_equal(a, b)`: each
+///   element of the text matches elements of at most one class of
+///   `pattern_equal`.
+///
+/// Otherwise matches can be missed, for example if an element of the text
+/// is a wildcard that matches every element of the pattern.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_cross;
+///
+/// let text = [" a", "b ", " c "];
+/// let pattern = ['b', 'c'];
+/// let result = knuth_morris_pratt_cross(&text, &pattern,
+///                                       |p, t| t.trim() == p.to_string(),
+///                                       |p, q| p == q);
+/// assert_eq!(result, Some(1));
+/// ```
+pub fn knuth_morris_pratt_cross<P, H, F, G>(text: &[H], pattern: &[P], mut equal: F,
+                                            mut pattern_equal: G)
+    -> Option<usize>
+    where F: FnMut(&P, &H) -> bool,
+          G: FnMut(&P, &P) -> bool
+{
+    if pattern.is_empty() {
+        return Some(0);
+    } else if pattern.len() > text.len() {
+        return None;
     }
-    test!("xyz", "a");
-    test!("xyz", "x");
-    test!("xyz", "y");
-    test!("xyz", "z");
-    test!("substrinstring", "string");
-    test!("abcαaαβγ", "αβ");
 
-    let result = knuth_morris_pratt(&[1729, 1, 1729, 3, 4], &[1729, 3]);
-    assert_eq!(result, Some(2));
+    let next = NextTable::new(pattern, &mut pattern_equal);
+    scan(text, pattern, &next, &mut 0, &mut 0, &mut equal)
 }
 
-#[test]
-fn test_by() {
-    let body = [[1, 2], [3, 4], [5, 6], [3, 4], [5, 10]];
-    let pat = [[7, 0], [12, 3]];
+/// Return `true` if `pattern` occurs as a substring of `text`.
+///
+/// The empty pattern is contained in every text.
+pub fn knuth_morris_pratt_contains<T>(text: &[T], pattern: &[T]) -> bool
+    where T: PartialEq
+{
+    knuth_morris_pratt_by(text, pattern, PartialEq::eq).is_some()
+}
 
-    let result = knuth_morris_pratt_by(&body, &pat, |a, b| a[0] + a[1] == b[0] + b[1]);
-    assert_eq!(result, Some(3));
+/// Return `true` if `text` starts with `pattern`, like `<[T]>::starts_with`.
+pub fn knuth_morris_pratt_prefix<T>(text: &[T], pattern: &[T]) -> bool
+    where T: PartialEq
+{
+    knuth_morris_pratt_prefix_by(text, pattern, PartialEq::eq)
+}
+
+/// Return `true` if `text` starts with `pattern`.
+///
+/// Use the function `equal` for equality comparison. Like in the search
+/// functions, it is called with an element of the pattern as the first
+/// argument and an element of the text as the second.
+pub fn knuth_morris_pratt_prefix_by<T, FEq>(text: &[T], pattern: &[T], mut equal: FEq)
+    -> bool
+    where FEq: FnMut(&T, &T) -> bool
+{
+    pattern.len() <= text.len() &&
+        pattern.iter().zip(text).all(|(p, t)| equal(p, t))
+}
+
+/// Return `true` if `pattern` occurs in `text` at offset `pos`, so that
+/// `text[pos..]` starts with `pattern`.
+///
+/// Return `false` if `pos` is past the end of the text.
+pub fn knuth_morris_pratt_at<T>(text: &[T], pattern: &[T], pos: usize) -> bool
+    where T: PartialEq
+{
+    knuth_morris_pratt_at_by(text, pattern, pos, PartialEq::eq)
+}
+
+/// Return `true` if `pattern` occurs in `text` at offset `pos`, so that
+/// `text[pos..]` starts with `pattern`.
+///
+/// Return `false` if `pos` is past the end of the text.
+///
+/// Use the function `equal` for equality comparison, like
+/// `knuth_morris_pratt_prefix_by`.
+pub fn knuth_morris_pratt_at_by<T, FEq>(text: &[T], pattern: &[T], pos: usize, equal: FEq)
+    -> bool
+    where FEq: FnMut(&T, &T) -> bool
+{
+    match text.get(pos..) {
+        Some(rest) => knuth_morris_pratt_prefix_by(rest, pattern, equal),
+        None => false,
+    }
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any. Return the range of the substring in the text inside a `Some`,
+/// so that the match is `&text[range]`. If the pattern is not found, return
+/// `None`.
+pub fn knuth_morris_pratt_range<T>(text: &[T], pattern: &[T])
+    -> Option<Range<usize>>
+    where T: PartialEq
+{
+    knuth_morris_pratt_by(text, pattern, PartialEq::eq)
+        .map(|start| start..start + pattern.len())
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// looking only at the first `limit` elements of the text. Return the start
+/// of the substring as an offset from the start of the text inside a
+/// `Some`. If the pattern is not found, return `None`.
+///
+/// A match must end at or before `limit`; a match that starts before the
+/// limit but would end after it is not found. No element after the limit is
+/// compared. If `limit` is past the end of the text, the whole text is
+/// searched.
+pub fn knuth_morris_pratt_limited<T>(text: &[T], pattern: &[T], limit: usize)
+    -> Option<usize>
+    where T: PartialEq
+{
+    let end = limit.min(text.len());
+    knuth_morris_pratt_by(&text[..end], pattern, PartialEq::eq)
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// starting the search at offset `start`. Return the start of the substring
+/// as an offset from the start of the text inside a `Some`. If the pattern is
+/// not found, or `start` is past the end of the text, return `None`.
+pub fn knuth_morris_pratt_from<T>(text: &[T], pattern: &[T], start: usize)
+    -> Option<usize>
+    where T: PartialEq
+{
+    if start > text.len() {
+        return None;
+    } else if pattern.is_empty() {
+        return Some(start);
+    } else if pattern.len() > text.len() - start {
+        return None;
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut j = start;
+    scan(text, pattern, &next, &mut 0, &mut j, &mut equal)
+}
+
+/// The position of a search that can be resumed with
+/// `knuth_morris_pratt_resume`.
+///
+/// It holds the position in the text and the length of the prefix of the
+/// pattern that is matched there, so it only makes sense for the same text
+/// and pattern.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cursor {
+    i: usize,
+    j: usize,
+}
+
+impl Cursor {
+    /// Create a cursor at the start of the text.
+    pub fn new() -> Self {
+        Cursor::default()
+    }
+
+    /// Return the offset in the text where the search resumes.
+    pub fn position(&self) -> usize {
+        self.j
+    }
+}
+
+/// Search for the next non-overlapping occurence of `pattern` as a
+/// substring of `text`, resuming the search at `cursor`. Return the start of
+/// the substring as an offset from the start of the text inside a `Some`,
+/// or `None` if there are no more matches, and the cursor to resume the
+/// search with after it.
+///
+/// Starting with `Cursor::new()` and threading the cursor through, the
+/// matches are the same as those of `knuth_morris_pratt_all`. Each call
+/// computes the shift table of the pattern again.
+///
+/// ```
+/// use knuth_morris_pratt::{knuth_morris_pratt_resume, Cursor};
+///
+/// let text = b"abcabcabc";
+/// let (first, cursor) = knuth_morris_pratt_resume(text, b"bc", Cursor::new());
+/// assert_eq!(first, Some(1));
+/// let (second, _) = knuth_morris_pratt_resume(text, b"bc", cursor);
+/// assert_eq!(second, Some(4));
+/// ```
+///
+/// ***Panics*** if the cursor is not from a search for the same pattern.
+pub fn knuth_morris_pratt_resume<T>(text: &[T], pattern: &[T], cursor: Cursor)
+    -> (Option<usize>, Cursor)
+    where T: PartialEq
+{
+    let Cursor { mut i, mut j } = cursor;
+    assert!(i < pattern.len() || i == 0,
+            "knuth_morris_pratt_resume: cursor does not belong to the pattern");
+    if pattern.is_empty() {
+        // the empty pattern matches at every offset
+        return if j <= text.len() {
+            (Some(j), Cursor { i, j: j + 1 })
+        } else {
+            (None, cursor)
+        };
+    } else if j >= text.len() {
+        return (None, cursor);
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let start = scan(text, pattern, &next, &mut i, &mut j, &mut equal);
+    if start.is_some() {
+        i = 0;
+    }
+    (start, Cursor { i, j })
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any. Return the start of the substring as an offset from the start of
+/// the text inside a `Some`. If the pattern is not found, return `None`.
+///
+/// The shift table is kept on the stack in an array of `N` elements if the
+/// pattern is shorter than `N`, else it is allocated on the heap (which
+/// requires the `alloc` feature). `knuth_morris_pratt` uses `N = 32`.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_with_stack;
+///
+/// let pattern = [7; 100];
+/// let mut text = vec![0; 1000];
+/// text.extend(&pattern);
+/// assert_eq!(knuth_morris_pratt_with_stack::<128, _>(&text, &pattern), Some(1000));
+/// ```
+pub fn knuth_morris_pratt_with_stack<const N: usize, T>(text: &[T], pattern: &[T])
+    -> Option<usize>
+    where T: PartialEq
+{
+    if pattern.is_empty() {
+        return Some(0);
+    } else if pattern.len() > text.len() {
+        return None;
+    }
+
+    let mut stack;
+    let mut heap;
+    let next: &mut [usize] = if pattern.len() < N {
+        stack = [0; N];
+        &mut stack
+    } else {
+        heap = NextTable::heap(pattern.len() + 1);
+        &mut heap
+    };
+    let mut equal = PartialEq::eq;
+    prepare_kmp(pattern, next, &mut equal);
+    scan(text, pattern, next, &mut 0, &mut 0, &mut equal)
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any. Return the start of the substring as an offset from the start of
+/// the text inside a `Some`. If the pattern is not found, return `None`.
+///
+/// Use `scratch` for the shift table, which needs `pattern.len() + 1`
+/// elements, so that the search never allocates.
+///
+/// ***Panics*** if `scratch` is too short. See `knuth_morris_pratt_try_in`.
+pub fn knuth_morris_pratt_in<T>(text: &[T], pattern: &[T], scratch: &mut [usize])
+    -> Option<usize>
+    where T: PartialEq
+{
+    match knuth_morris_pratt_try_in(text, pattern, scratch) {
+        Ok(result) => result,
+        Err(e) => panic!("knuth_morris_pratt_in: {}", e),
+    }
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, like `knuth_morris_pratt_in`. Return the start of the substring
+/// as an offset from the start of the text inside a `Some`. If the pattern
+/// is not found, return `None`.
+///
+/// Return `KmpError::ScratchTooSmall` if `scratch` is too short for the
+/// shift table.
+///
+/// ```
+/// use knuth_morris_pratt::{knuth_morris_pratt_try_in, KmpError};
+///
+/// let mut scratch = [0; 4];
+/// assert_eq!(knuth_morris_pratt_try_in(b"abcab", b"cab", &mut scratch).ok(), Some(Some(2)));
+/// match knuth_morris_pratt_try_in(b"abcab", b"abcd", &mut scratch) {
+///     Err(KmpError::ScratchTooSmall { needed: 5, got: 4 }) => {}
+///     _ => panic!(),
+/// }
+/// ```
+pub fn knuth_morris_pratt_try_in<T>(text: &[T], pattern: &[T], scratch: &mut [usize])
+    -> Result<Option<usize>, KmpError>
+    where T: PartialEq
+{
+    if scratch.len() <= pattern.len() {
+        return Err(KmpError::ScratchTooSmall {
+            needed: pattern.len() + 1,
+            got: scratch.len(),
+        });
+    } else if pattern.is_empty() {
+        return Ok(Some(0));
+    } else if pattern.len() > text.len() {
+        return Ok(None);
+    }
+
+    let mut equal = PartialEq::eq;
+    prepare_kmp(pattern, scratch, &mut equal);
+    Ok(scan(text, pattern, scratch, &mut 0, &mut 0, &mut equal))
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, comparing the numbers like `f64::total_cmp`. Return the start of
+/// the substring as an offset from the start of the text inside a `Some`.
+/// If the pattern is not found, return `None`.
+///
+/// Two numbers are equal if they have the same bit pattern, so unlike with
+/// `==`, a `NaN` matches a `NaN` with the same sign and payload, and `0.0`
+/// does not match `-0.0`. This is an equivalence relation, as KMP requires.
+///
+/// ```
+/// use knuth_morris_pratt::{knuth_morris_pratt_by, knuth_morris_pratt_total_f64};
+///
+/// let text = [1.0, f64::NAN, 2.0];
+/// assert_eq!(knuth_morris_pratt_total_f64(&text, &[f64::NAN, 2.0]), Some(1));
+/// assert_eq!(knuth_morris_pratt_by(&text, &[f64::NAN, 2.0], f64::eq), None);
+/// ```
+pub fn knuth_morris_pratt_total_f64(text: &[f64], pattern: &[f64]) -> Option<usize> {
+    // `total_cmp` is equal exactly for equal bits
+    knuth_morris_pratt_by(text, pattern, |a, b| a.to_bits() == b.to_bits())
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, ignoring ASCII case. Return the start of the substring as an
+/// offset from the start of the text inside a `Some`. If the pattern is not
+/// found, return `None`.
+///
+/// Only the ASCII letters `a-z` and `A-Z` are compared case insensitively;
+/// this does not do Unicode case folding.
+pub fn knuth_morris_pratt_ascii_case_insensitive(text: &[u8], pattern: &[u8])
+    -> Option<usize>
+{
+    knuth_morris_pratt_by(text, pattern, u8::eq_ignore_ascii_case)
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, where the element `wildcard` in the pattern matches any element
+/// of the text. Return the start of the substring as an offset from the
+/// start of the text inside a `Some`. If the pattern is not found, return
+/// `None`.
+///
+/// The shift table can not be used with wildcards, since a wildcard makes
+/// matching non-transitive. If the pattern contains the wildcard, the pattern
+/// is compared with the text at every offset instead, which takes
+/// O(`text.len()` × `pattern.len()`) time in the worst case. Without
+/// wildcards, this is a regular linear time search.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_wildcard;
+///
+/// assert_eq!(knuth_morris_pratt_wildcard(b"abaxc", b"a?c", &b'?'), Some(2));
+/// ```
+pub fn knuth_morris_pratt_wildcard<T>(text: &[T], pattern: &[T], wildcard: &T)
+    -> Option<usize>
+    where T: PartialEq
+{
+    if !pattern.contains(wildcard) {
+        return knuth_morris_pratt_by(text, pattern, PartialEq::eq);
+    } else if pattern.len() > text.len() {
+        return None;
+    }
+    (0..text.len() - pattern.len() + 1).find(|&start| {
+        pattern.iter().zip(&text[start..])
+               .all(|(p, t)| p == wildcard || p == t)
+    })
+}
+
+/// Search for the first occurence of a pattern of element classes as a
+/// substring of `text`, if any, where `classes[k]` is the set of elements
+/// that match at position `k` of the pattern. Return the start of the
+/// substring as an offset from the start of the text inside a `Some`. If
+/// the pattern is not found, return `None`. An empty class matches nothing.
+///
+/// Like with `knuth_morris_pratt_wildcard`, the shift table can not be used
+/// for classes of several elements, since overlapping classes make matching
+/// non-transitive. The pattern is then compared with the text at every
+/// offset instead, which takes O(`text.len()` × `pattern.len()`) time in the
+/// worst case; this is not KMP. If every class is a single element, this is
+/// a regular linear time search.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_classes;
+///
+/// assert_eq!(knuth_morris_pratt_classes(b"abaxc", &[b"a", b"xy", b"c"]), Some(2));
+/// ```
+pub fn knuth_morris_pratt_classes<T>(text: &[T], classes: &[&[T]]) -> Option<usize>
+    where T: PartialEq
+{
+    if classes.iter().all(|class| class.len() == 1) {
+        return knuth_morris_pratt_cross(text, classes,
+                                        |class, elem| class[0] == *elem,
+                                        |a, b| a[0] == b[0]);
+    } else if classes.len() > text.len() {
+        return None;
+    }
+    (0..text.len() - classes.len() + 1).find(|&start| {
+        classes.iter().zip(&text[start..])
+               .all(|(class, t)| class.contains(t))
+    })
+}
+
+/// Search for the first occurence of `pattern` in the sequence of elements
+/// `text`, if any. Return the start of the match as the number of elements
+/// before it inside a `Some`. If the pattern is not found, return `None`.
+///
+/// The text is only iterated once, up to the end of the first match. The
+/// pattern must still be a slice, since computing its shift table needs
+/// random access to it. The elements of the text can be values or
+/// references, anything that borrows as the pattern's element type.
+pub fn knuth_morris_pratt_iter<I, T>(text: I, pattern: &[T]) -> Option<usize>
+    where I: IntoIterator,
+          I::Item: Borrow<T>,
+          T: PartialEq
+{
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut i = 0;
+    for (count, elem) in text.into_iter().enumerate() {
+        if feed(elem.borrow(), pattern, &next, &mut i, &mut equal) {
+            return Some(count + 1 - pattern.len());
+        }
+    }
+    None
+}
+
+/// Substring search for iterators.
+///
+/// Implemented for all iterators, so that it can be called as a method:
+/// `iter.kmp_find(pattern)`.
+pub trait KmpIterExt: Iterator {
+    /// Search for the first occurence of `pattern` in the elements of the
+    /// iterator, like `knuth_morris_pratt_iter`. Return the start of the
+    /// match as the index of its first element inside a `Some`. If the
+    /// pattern is not found, return `None`.
+    ///
+    /// The iterator is consumed up to the end of the first match, and
+    /// entirely if there is none, without collecting its elements.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::KmpIterExt;
+    ///
+    /// let squares = (0..).map(|x: u32| x * x % 10);
+    /// assert_eq!(squares.kmp_find(&[6, 9, 4]), Some(6));
+    /// ```
+    fn kmp_find(self, pattern: &[Self::Item]) -> Option<usize>
+        where Self: Sized,
+              Self::Item: PartialEq
+    {
+        knuth_morris_pratt_iter(self, pattern)
+    }
+}
+
+impl<I> KmpIterExt for I
+    where I: Iterator
+{ }
+
+/// Find the longest prefix of `pattern` that occurs in `text`. Return the
+/// offset of its first occurence in the text and its length.
+///
+/// If the whole pattern occurs in the text, this is the first match. If not
+/// even the first element of the pattern occurs, return `(0, 0)`. This is a
+/// debugging aid, for finding out how far a pattern got towards matching.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_longest_prefix_match;
+///
+/// assert_eq!(knuth_morris_pratt_longest_prefix_match(b"substrinstring", b"strix"), (3, 4));
+/// ```
+pub fn knuth_morris_pratt_longest_prefix_match<T>(text: &[T], pattern: &[T])
+    -> (usize, usize)
+    where T: PartialEq
+{
+    if pattern.is_empty() {
+        return (0, 0);
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    let mut i = 0;
+    let mut longest = (0, 0);
+    for (j, elem) in text.iter().enumerate() {
+        let is_match = feed(elem, pattern, &next, &mut i, &mut equal);
+        if i > longest.1 {
+            longest = (j + 1 - i, i);
+        }
+        if is_match {
+            break;
+        }
+    }
+    longest
+}
+
+/// Search for the first occurence of `pattern` in the text made up of the
+/// slices `chunks` one after another, if any. Return the start of the match
+/// as an offset from the start of the first chunk inside a `Some`. If the
+/// pattern is not found, return `None`.
+///
+/// Matches that span multiple chunks are found, so this can search a
+/// `VecDeque` without copying it:
+///
+/// ```
+/// use std::collections::VecDeque;
+/// use knuth_morris_pratt::knuth_morris_pratt_slices;
+///
+/// let mut buffer = VecDeque::with_capacity(8);
+/// buffer.extend(b"xxxxxx");
+/// buffer.drain(..4);
+/// buffer.extend(b"abcd");
+/// let (front, back) = buffer.as_slices();
+/// assert_eq!(knuth_morris_pratt_slices(&[front, back], b"xa"), Some(1));
+/// ```
+pub fn knuth_morris_pratt_slices<T>(chunks: &[&[T]], pattern: &[T]) -> Option<usize>
+    where T: PartialEq
+{
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    let mut equal = PartialEq::eq;
+    let next = NextTable::new(pattern, &mut equal);
+    // the automaton state is kept across chunks
+    let mut i = 0;
+    // offset of the start of the current chunk
+    let mut offset = 0;
+    for chunk in chunks {
+        let mut j = 0;
+        if scan_end(*chunk, pattern, &next, &mut i, &mut j, &mut equal) {
+            return Some(offset + j - pattern.len());
+        }
+        offset += chunk.len();
+    }
+    None
+}
+
+/// Search for the last occurence of `pattern` as a substring of `text`,
+/// if any. Return the start of the substring as an offset from the start of
+/// the text inside a `Some`. If the pattern is not found, return `None`.
+///
+/// The empty pattern matches at `text.len()`.
+pub fn knuth_morris_pratt_rev<T>(text: &[T], pattern: &[T]) -> Option<usize>
+    where T: PartialEq
+{
+    knuth_morris_pratt_rev_by(text, pattern, PartialEq::eq)
+}
+
+/// The direction of a search with `search`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Search for the first occurence, like `knuth_morris_pratt`.
+    Forward,
+    /// Search for the last occurence, like `knuth_morris_pratt_rev`.
+    Backward,
+}
+
+/// Search for the first or the last occurence of `pattern` as a substring
+/// of `text`, if any, depending on `dir`. Return the start of the substring
+/// as an offset from the start of the text inside a `Some`. If the pattern
+/// is not found, return `None`.
+///
+/// This is `knuth_morris_pratt` or `knuth_morris_pratt_rev`, for a direction
+/// that is picked at runtime.
+///
+/// ```
+/// use knuth_morris_pratt::{search, Direction};
+///
+/// let text = b"a,b,c";
+/// assert_eq!(search(text, b",", Direction::Forward), Some(1));
+/// assert_eq!(search(text, b",", Direction::Backward), Some(3));
+/// ```
+pub fn search<T>(text: &[T], pattern: &[T], dir: Direction) -> Option<usize>
+    where T: PartialEq
+{
+    match dir {
+        // `knuth_morris_pratt` with the weaker bound
+        Direction::Forward => knuth_morris_pratt_by(text, pattern, PartialEq::eq),
+        Direction::Backward => knuth_morris_pratt_rev(text, pattern),
+    }
+}
+
+/// Search for the last occurence of `pattern` as a substring of `text`,
+/// if any. Return the start of the substring as an offset from the start of
+/// the text inside a `Some`. If the pattern is not found, return `None`.
+///
+/// The empty pattern matches at `text.len()`.
+///
+/// Use the function `equal` for equality comparison.
+pub fn knuth_morris_pratt_rev_by<T, FEq>(text: &[T], pattern: &[T], mut equal: FEq)
+    -> Option<usize>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    // search the reversed pattern in the reversed text
+    if pattern.is_empty() {
+        return Some(text.len());
+    } else if pattern.len() > text.len() {
+        return None;
+    }
+
+    let next = NextTable::new(Rev(pattern), &mut equal);
+    scan(Rev(text), Rev(pattern), &next, &mut 0, &mut 0, &mut equal)
+        .map(|rev_start| text.len() - rev_start - pattern.len())
+}
+
+/// Search for the last occurence of `pattern` as a substring of `text` that
+/// ends at or before offset `end`, so that it is contained in `text[..end]`.
+/// Return the start of the substring as an offset from the start of the text
+/// inside a `Some`. If the pattern is not found, return `None`.
+///
+/// If `end` is past the end of the text, the whole text is searched. The
+/// empty pattern matches at `end`.
+pub fn knuth_morris_pratt_rev_before<T>(text: &[T], pattern: &[T], end: usize)
+    -> Option<usize>
+    where T: PartialEq
+{
+    let end = end.min(text.len());
+    knuth_morris_pratt_rev_by(&text[..end], pattern, PartialEq::eq)
+}
+
+/// The KMP shift table of a pattern.
+///
+/// The table has `pattern.len() + 1` entries. After a mismatch at position
+/// `i` of the pattern, the search continues by comparing position
+/// `table[i]` of the pattern with the same element of the text. The entry
+/// at position 0 is the sentinel value `!0`, which means that the search
+/// continues with the next element of the text instead.
+///
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefixTable {
+    next: Vec<usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl PrefixTable {
+    /// Compute the shift table of `pattern`.
+    pub fn new<T>(pattern: &[T]) -> Self
+        where T: PartialEq
+    {
+        PrefixTable::new_by(pattern, PartialEq::eq)
+    }
+
+    /// Compute the shift table of `pattern`.
+    ///
+    /// Use the function `equal` for equality comparison.
+    pub fn new_by<T, FEq>(pattern: &[T], mut equal: FEq) -> Self
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        let mut next = vec![0; pattern.len() + 1];
+        prepare_kmp(pattern, &mut next, &mut equal);
+        PrefixTable {
+            next,
+        }
+    }
+
+    /// Return the table as a slice.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.next
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for PrefixTable {
+    type Target = [usize];
+    fn deref(&self) -> &[usize] {
+        &self.next
+    }
+}
+
+/// Compute the shift table of the byte pattern `pattern` in a `const`
+/// context, like `PrefixTable::new`, so that the table of a fixed pattern
+/// can be a `static`. The table has `N = M + 1` entries.
+///
+/// ***Panics*** (at compile time, in a `const` context) if `N` is not
+/// `M + 1`.
+///
+/// ```
+/// use knuth_morris_pratt::{prepare_kmp_const, search_with_table};
+///
+/// static PATTERN: &[u8; 8] = b"GCAGAGAG";
+/// static TABLE: [usize; 9] = prepare_kmp_const(PATTERN);
+///
+/// assert_eq!(search_with_table(b"GCATCGCAGAGAGTATACAGTACG", PATTERN, &TABLE), Some(5));
+/// ```
+pub const fn prepare_kmp_const<const M: usize, const N: usize>(pattern: &[u8; M])
+    -> [usize; N]
+{
+    assert!(N == M + 1, "prepare_kmp_const: the table must have one more entry than the pattern");
+    // the same steps as `prepare_kmp`
+    let mut next = [0; N];
+    let mut i = 0;
+    let mut j = !0;
+    next[0] = !0;
+    while i < M {
+        while j != !0 && pattern[i] != pattern[j] {
+            j = next[j];
+        }
+        i += 1;
+        j = j.wrapping_add(1);
+        if i != M && pattern[i] == pattern[j] {
+            next[i] = next[j];
+        } else {
+            next[i] = j;
+        }
+    }
+    next
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, using its precomputed shift table `table`. Return the start of the
+/// substring as an offset from the start of the text inside a `Some`. If the
+/// pattern is not found, return `None`.
+///
+/// `table` must be the shift table of `pattern`, from `PrefixTable` or
+/// `prepare_kmp_const`.
+pub fn search_with_table<T>(text: &[T], pattern: &[T], table: &[usize])
+    -> Option<usize>
+    where T: PartialEq
+{
+    debug_assert_eq!(table.len(), pattern.len() + 1,
+                     "search_with_table: table does not belong to the pattern");
+    find_with_table(text, pattern, table, &mut PartialEq::eq)
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// if any, like `knuth_morris_pratt`, and also return the shift table of
+/// the pattern, as in `PrefixTable`.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_with_table;
+///
+/// let (index, table) = knuth_morris_pratt_with_table(b"abcabab", b"abab");
+/// assert_eq!(index, Some(3));
+/// assert_eq!(table, [!0, 0, !0, 0, 2]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_with_table<T>(text: &[T], pattern: &[T])
+    -> (Option<usize>, Vec<usize>)
+    where T: PartialEq
+{
+    let table = PrefixTable::new(pattern);
+    let index = search_with_table(text, pattern, &table);
+    (index, table.next)
+}
+
+/// Search for the first occurence of any of `patterns` as a substring of
+/// `text`, if any. Return the start of the substring as an offset from the
+/// start of the text, and the index of the pattern that matched, inside a
+/// `Some`. If no pattern is found, return `None`.
+///
+/// If several patterns match at the same offset, the one with the lowest
+/// index is returned.
+///
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_any<T>(text: &[T], patterns: &[&[T]])
+    -> Option<(usize, usize)>
+    where T: PartialEq
+{
+    let tables = patterns.iter().map(|p| PrefixTable::new(p)).collect::<Vec<_>>();
+    let mut first: Option<(usize, usize)> = None;
+    for (index, (pattern, table)) in patterns.iter().zip(&tables).enumerate() {
+        // only a match that starts before the best one so far is of interest
+        let end = match first {
+            Some((0, _)) => break,
+            Some((start, _)) => (start - 1 + pattern.len()).min(text.len()),
+            None => text.len(),
+        };
+        if let Some(start) = search_with_table(&text[..end], pattern, table) {
+            first = Some((start, index));
+        }
+    }
+    first
+}
+
+/// Replace all non-overlapping occurences of `pattern` in `text` with
+/// `replacement`, and return the result as a new vector.
+///
+/// Like `str::replace`, the empty pattern matches at every offset, so the
+/// replacement is inserted before each element and at the end.
+///
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_replace<T>(text: &[T], pattern: &[T], replacement: &[T])
+    -> Vec<T>
+    where T: Clone + PartialEq
+{
+    knuth_morris_pratt_replace_cow(text, pattern, replacement).into_owned()
+}
+
+/// Replace all non-overlapping occurences of `pattern` in `text` with
+/// `replacement`, like `knuth_morris_pratt_replace`. Return the text itself
+/// if there are no matches, and a new vector otherwise.
+///
+/// The search stops at the first match before allocating, so a text without
+/// matches is only scanned once and never copied.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use knuth_morris_pratt::knuth_morris_pratt_replace_cow;
+///
+/// let text = b"no matches here";
+/// assert!(matches!(knuth_morris_pratt_replace_cow(text, b"xyz", b"-"), Cow::Borrowed(_)));
+/// assert_eq!(knuth_morris_pratt_replace_cow(text, b"here", b"there"), &b"no matches there"[..]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_replace_cow<'a, T>(text: &'a [T], pattern: &[T], replacement: &[T])
+    -> Cow<'a, [T]>
+    where T: Clone + PartialEq
+{
+    let mut matches = knuth_morris_pratt_all(text, pattern);
+    let first = match matches.next() {
+        Some(start) => start,
+        None => return Cow::Borrowed(text),
+    };
+    let mut result = Vec::with_capacity(text.len());
+    let mut last_end = 0;
+    for start in Some(first).into_iter().chain(matches) {
+        result.extend_from_slice(&text[last_end..start]);
+        result.extend_from_slice(replacement);
+        last_end = start + pattern.len();
+    }
+    result.extend_from_slice(&text[last_end..]);
+    Cow::Owned(result)
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`
+/// by comparing the pattern with the text at every offset. This is the
+/// simple O(nm) algorithm; it is meant as a reference for testing.
+///
+/// Requires the `testing` feature.
+#[cfg(feature = "testing")]
+pub fn naive_search<T>(text: &[T], pattern: &[T]) -> Option<usize>
+    where T: PartialEq
+{
+    if pattern.len() > text.len() {
+        return None;
+    }
+    (0..text.len() - pattern.len() + 1).find(|&i| &text[i..i + pattern.len()] == pattern)
+}
+
+// more thorough tests in the tests/ directory
+#[test]
+fn basic_test() {
+    let body = "G";
+    let pattern = "GCAGAGAG";
+    knuth_morris_pratt(body.as_bytes(), pattern.as_bytes());
+    macro_rules! test {
+        ($body:expr, $pattern:expr) => {
+            assert_eq!($body.find($pattern),
+                       knuth_morris_pratt($body.as_bytes(), $pattern.as_bytes()),
+                       "assertion failed for body={}, pattern={}",
+                       $body, $pattern)
+        }
+    }
+    test!("xyz", "a");
+    test!("xyz", "x");
+    test!("xyz", "y");
+    test!("xyz", "z");
+    test!("substrinstring", "string");
+    test!("abcαaαβγ", "αβ");
+
+    let result = knuth_morris_pratt(&[1729, 1, 1729, 3, 4], &[1729, 3]);
+    assert_eq!(result, Some(2));
+}
+
+#[test]
+fn test_by() {
+    let body = [[1, 2], [3, 4], [5, 6], [3, 4], [5, 10]];
+    let pat = [[7, 0], [12, 3]];
+
+    let result = knuth_morris_pratt_by(&body, &pat, |a, b| a[0] + a[1] == b[0] + b[1]);
+    assert_eq!(result, Some(3));
+}
+
+#[test]
+fn test_cross() {
+    #[derive(PartialEq)]
+    enum Token {
+        Open,
+        Close,
+        Word,
+    }
+    struct RawEvent {
+        byte: u8,
+    }
+    let token_eq = |t: &Token, e: &RawEvent| match *t {
+        Token::Open => e.byte == b'(',
+        Token::Close => e.byte == b')',
+        Token::Word => e.byte.is_ascii_alphabetic(),
+    };
+    let events = |s: &[u8]| s.iter().map(|&byte| RawEvent { byte }).collect::<Vec<_>>();
+    let find = |text: &[u8], pattern: &[Token]| {
+        knuth_morris_pratt_cross(&events(text), pattern, token_eq, Token::eq)
+    };
+
+    assert_eq!(find(b"(a)(bc)", &[Token::Open, Token::Word, Token::Word, Token::Close]),
+               Some(3));
+    assert_eq!(find(b"(ab(ab(abc)", &[Token::Open, Token::Word, Token::Word, Token::Close]),
+               None);
+    assert_eq!(find(b"((ab)", &[Token::Open, Token::Word]), Some(1));
+    assert_eq!(find(b"ab(", &[Token::Word, Token::Open]), Some(1));
+    assert_eq!(find(b"ab", &[]), Some(0));
+    assert_eq!(find(b"a", &[Token::Word, Token::Word]), None);
+}
+
+#[test]
+fn test_cross_text_matches_two_classes() {
+    // `X` in the text matches both `a` and `b`, which are different in the
+    // pattern. The contract rules this out: the shift table skips the
+    // match at 1, that a naive search finds
+    let equal = |&p: &char, &t: &char| t == p || (t == 'X' && (p == 'a' || p == 'b'));
+    let text = ['a', 'X', 'b', 'c'];
+    let pattern = ['a', 'b', 'c'];
+    let naive = (0..text.len() - pattern.len() + 1).find(|&start| {
+        pattern.iter().zip(&text[start..]).all(|(p, t)| equal(p, t))
+    });
+    assert_eq!(naive, Some(1));
+    assert_eq!(knuth_morris_pratt_cross(&text, &pattern, equal, char::eq), None);
+
+    // when `X` only matches `b`, the search agrees with the naive one
+    let equal = |&p: &char, &t: &char| t == p || (t == 'X' && p == 'b');
+    assert_eq!(knuth_morris_pratt_cross(&text, &pattern, equal, char::eq), None);
+    assert_eq!(knuth_morris_pratt_cross(&['a', 'a', 'X', 'c'], &pattern, equal, char::eq),
+               Some(1));
+}
+
+#[test]
+fn test_single_element() {
+    assert_eq!(knuth_morris_pratt(b"abcabc", b"c"), Some(2));
+    assert_eq!(knuth_morris_pratt(b"abcabc", b"d"), None);
+    assert_eq!(knuth_morris_pratt(b"", b"d"), None);
+    // the pattern element is the first argument of `equal`
+    let text = [3, 5, 10, 20];
+    assert_eq!(knuth_morris_pratt_by(&text, &[3], |p, t| t % p == 1), Some(2));
+}
+
+#[test]
+fn test_adaptive() {
+    let texts = ["", "a", "substrinstring", "aaaaaaaab", "abababcabababab", "xyzabababcxyz"];
+    let patterns = ["", "a", "ab", "aab", "ababc", "abababab", "xyzz", "string", "bcx"];
+    for text in &texts {
+        for pattern in &patterns {
+            assert_eq!(knuth_morris_pratt_adaptive(text.as_bytes(), pattern.as_bytes()),
+                       knuth_morris_pratt(text.as_bytes(), pattern.as_bytes()),
+                       "text={:?} pattern={:?}", text, pattern);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_max_prefix_border() {
+    let border = |pattern: &[u8]| max_prefix_border(pattern, &mut u8::eq);
+    assert_eq!(border(b""), 0);
+    assert_eq!(border(b"a"), 0);
+    assert_eq!(border(b"abcd"), 0);
+    assert_eq!(border(b"abca"), 1);
+    assert_eq!(border(b"aaaab"), 3);
+    assert_eq!(border(b"abababc"), 4);
+    assert_eq!(border(b"abaabab"), 3);
+}
+
+#[test]
+fn test_by_periodic_exhaustive() {
+    // all short texts and patterns over an alphabet where `a` and `A` are
+    // equal but not identical
+    fn strings(max_len: usize) -> Vec<Vec<u8>> {
+        let mut all = vec![vec![]];
+        let mut last = vec![vec![]];
+        for _ in 0..max_len {
+            last = last.iter()
+                       .flat_map(|s: &Vec<u8>| b"aAb".iter().map(move |&c| {
+                           let mut s = s.clone();
+                           s.push(c);
+                           s
+                       }))
+                       .collect();
+            all.extend(last.iter().cloned());
+        }
+        all
+    }
+    let equal = |a: &u8, b: &u8| a.eq_ignore_ascii_case(b);
+    let patterns = strings(5);
+    for text in &strings(6) {
+        for pattern in &patterns {
+            let naive = (0..text.len() + 1)
+                .filter(|&i| text.len() - i >= pattern.len())
+                .find(|&i| pattern.iter().zip(&text[i..]).all(|(p, t)| equal(p, t)));
+            assert_eq!(knuth_morris_pratt_by(text, pattern, equal), naive,
+                       "text={:?} pattern={:?}", text, pattern);
+        }
+    }
+}
+
+#[test]
+fn test_by_variants() {
+    let body = [[1, 2], [3, 4], [5, 6], [3, 4], [5, 10], [0, 7], [15, 0]];
+    let pat = [[7, 0], [12, 3]];
+    let sum_eq = |a: &[i32; 2], b: &[i32; 2]| a[0] + a[1] == b[0] + b[1];
+
+    let result = knuth_morris_pratt_all_by(&body, &pat, sum_eq).collect::<Vec<_>>();
+    assert_eq!(result, vec![3, 5]);
+    assert_eq!(knuth_morris_pratt_count_by(&body, &pat, sum_eq), 2);
+    assert_eq!(knuth_morris_pratt_rev_by(&body, &pat, sum_eq), Some(5));
+}
+
+#[test]
+fn test_all() {
+    let result = knuth_morris_pratt_all(b"aaaaa", b"aa").collect::<Vec<_>>();
+    assert_eq!(result, vec![0, 2]);
+    let result = knuth_morris_pratt_all(b"abc", b"").collect::<Vec<_>>();
+    assert_eq!(result, vec![0, 1, 2, 3]);
+    let result = knuth_morris_pratt_all(b"abc", b"abcd").collect::<Vec<_>>();
+    assert_eq!(result, Vec::<usize>::new());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_all_into() {
+    let mut out = vec![7];
+    knuth_morris_pratt_all_into(b"aaaaa", b"aa", &mut out);
+    assert_eq!(out, [7, 0, 2]);
+    knuth_morris_pratt_all_into(b"abc", b"", &mut out);
+    assert_eq!(out, [7, 0, 2, 0, 1, 2, 3]);
+    knuth_morris_pratt_all_into(b"abc", b"abcd", &mut out);
+    assert_eq!(out.len(), 7);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_all_robust() {
+    let texts: [&[u8]; 4] = [b"aaaaa", b"abaab", b"abcabca", b""];
+    for text in &texts {
+        for pattern in &[&b""[..], b"a", b"aa", b"abca"] {
+            assert_eq!(knuth_morris_pratt_all_robust(text, pattern, u8::eq),
+                       knuth_morris_pratt_all(text, pattern).collect::<Vec<_>>());
+        }
+    }
+
+    // every reported match holds element by element, for a comparator that
+    // is not transitive
+    let near = |a: &u8, b: &u8| (*a as i32 - *b as i32).abs() <= 1;
+    assert_eq!(knuth_morris_pratt_all_by(&[2, 1, 2, 0], &[3, 2, 0], near).next(), Some(1));
+    assert_eq!(knuth_morris_pratt_all_robust(&[2, 1, 2, 0], &[3, 2, 0], near), Vec::<usize>::new());
+    let text = [1, 2, 3, 2, 1, 0, 1, 2, 3, 4, 3, 2, 1, 2, 3];
+    for len in 1..6 {
+        for pattern in text.windows(len) {
+            let starts = knuth_morris_pratt_all_robust(&text, pattern, near);
+            assert!(starts.windows(2).all(|w| w[0] + len <= w[1]));
+            for &start in &starts {
+                assert!(pattern.iter().zip(&text[start..]).all(|(p, t)| near(p, t)));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_histogram() {
+    let text = b"aXaXaXaXaXaX";
+    assert_eq!(knuth_morris_pratt_histogram(text, b"a", 3), [2, 2, 2]);
+    assert_eq!(knuth_morris_pratt_histogram(text, b"a", 1), [6]);
+    assert_eq!(knuth_morris_pratt_histogram(text, b"aXa", 2), [2, 1]);
+    assert_eq!(knuth_morris_pratt_histogram(text, b"a", 24).iter().sum::<usize>(), 6);
+    assert_eq!(knuth_morris_pratt_histogram(text, b"b", 2), [0, 0]);
+    assert_eq!(knuth_morris_pratt_histogram(text, b"a", 0), Vec::<usize>::new());
+    // the empty pattern also matches at the end of the text
+    assert_eq!(knuth_morris_pratt_histogram(b"abcd", b"", 2), [2, 3]);
+    assert_eq!(knuth_morris_pratt_histogram(b"", b"", 2), [1, 0]);
+    assert_eq!(knuth_morris_pratt_histogram(b"", b"a", 2), [0, 0]);
+}
+
+#[test]
+fn test_resume() {
+    let texts: [&[u8]; 5] = [b"aaaaa", b"abaabaab", b"abcabca", b"a", b""];
+    for text in &texts {
+        for pattern in &[&b""[..], b"a", b"aa", b"aab", b"abca"] {
+            let mut cursor = Cursor::new();
+            let mut matches = knuth_morris_pratt_all(text, pattern);
+            loop {
+                let (start, next) = knuth_morris_pratt_resume(text, pattern, cursor);
+                assert_eq!(start, matches.next());
+                if start.is_none() {
+                    assert_eq!(knuth_morris_pratt_resume(text, pattern, next).0, None);
+                    break;
+                }
+                assert_eq!(next.position(), start.unwrap() + pattern.len().max(1));
+                cursor = next;
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_resume_wrong_pattern() {
+    let (_, cursor) = knuth_morris_pratt_resume(b"abcab", b"abc", Cursor::new());
+    let (_, cursor) = knuth_morris_pratt_resume(b"abcab", b"abc", cursor);
+    assert_eq!(cursor.position(), 5);
+    knuth_morris_pratt_resume(b"abcab", b"a", cursor);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_tally() {
+    let patterns: [&[u8]; 5] = [b"a", b"aa", b"ab", b"", b"abc"];
+    let searchers = patterns.iter().map(|p| Searcher::new(p.to_vec())).collect::<Vec<_>>();
+    let texts: [&[u8]; 4] = [b"aaaaa", b"abaab", b"abcabca", b""];
+    for text in &texts {
+        let counts = patterns.iter().map(|p| knuth_morris_pratt_count(text, p))
+                             .collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt_tally(text, &patterns), counts);
+        assert_eq!(knuth_morris_pratt_tally_searchers(text, &searchers), counts);
+    }
+    assert_eq!(knuth_morris_pratt_tally(b"aaaaa", &patterns), [5, 2, 0, 6, 0]);
+    assert_eq!(knuth_morris_pratt_tally::<u8>(b"abc", &[]), Vec::<usize>::new());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_searcher() {
+    let searcher = Searcher::new(b"string".to_vec());
+    assert_eq!(searcher.find(b"substrinstring"), Some(8));
+    assert_eq!(searcher.find(b"strin"), None);
+    assert_eq!(searcher.find(b"stringstring"), Some(0));
+
+    let searcher = Searcher::new(Vec::new());
+    assert_eq!(searcher.find(b"xyz"), Some(0));
+
+    let body = [[1, 2], [3, 4], [5, 6], [3, 4], [5, 10]];
+    let sum_eq = |a: &[i32; 2], b: &[i32; 2]| a[0] + a[1] == b[0] + b[1];
+    let searcher = Searcher::new_by(vec![[7, 0], [12, 3]], sum_eq);
+    assert_eq!(searcher.find_by(&body, sum_eq), Some(3));
+
+    // the table depends on the equality, so it must be searched with the same
+    let ci = |a: &u8, b: &u8| a.eq_ignore_ascii_case(b);
+    let searcher = Searcher::new_by(b"aA".to_vec(), ci);
+    assert_eq!(searcher.find_by(b"aaA", ci), Some(0));
+    assert_eq!(Searcher::new(b"aA".to_vec()).find(b"aaA"), Some(1));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_searcher_by_key() {
+    struct Record {
+        id: u32,
+        payload: [u8; 1024],
+    }
+    let record = |id| Record { id, payload: [id as u8; 1024] };
+    let key = |r: &Record| r.id;
+
+    let searcher = Searcher::new_by_key(vec![record(1), record(2), record(1)], key);
+    let mut text = [0, 1, 2, 3, 1, 2].iter().cloned().map(record).collect::<Vec<_>>();
+    assert_eq!(searcher.find_by_key(&text, key), None);
+    text.push(record(1));
+    assert_eq!(searcher.find_by_key(&text, key), Some(4));
+
+    // only the key is compared
+    text[5].payload = [0; 1024];
+    assert_eq!(searcher.find_by_key(&text, key), Some(4));
+    assert_eq!(searcher.table(), &[!0, 0, !0, 1]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_searcher_eq_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::string::ToString;
+
+    fn hash<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let searcher = Searcher::new(b"abab".to_vec());
+    let other = Searcher::new(b"abab".to_vec());
+    assert!(searcher == other);
+    assert_eq!(hash(&searcher), hash(&other));
+    assert!(searcher == searcher.clone());
+    assert!(searcher != Searcher::new(b"abba".to_vec()));
+    // same pattern, but a different table
+    let ci = Searcher::new_by(b"aA".to_vec(), |a, b| a.eq_ignore_ascii_case(b));
+    assert!(ci != Searcher::new(b"aA".to_vec()));
+    assert_eq!(hash(&ci), hash(&Searcher::new(b"aA".to_vec())));
+
+    let long = Searcher::new(vec![1; 100]);
+    assert!(long.clone() == long);
+    assert_eq!(long.clone().table(), long.table());
+
+    assert_eq!(format!("{:?}", Searcher::new(vec![1, 2])), "Searcher { pattern: [1, 2] }");
+    let alternate = format!("{:#?}", Searcher::new(vec![1, 2]));
+    assert!(alternate.contains("table"), "{}", alternate);
+    assert!(alternate.contains(&(!0usize).to_string()), "{}", alternate);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_searcher_table() {
+    let searcher = Searcher::new(b"abab".to_vec());
+    assert_eq!(searcher.pattern(), b"abab");
+    assert_eq!(searcher.table(), &[!0, 0, !0, 0, 2]);
+    assert_eq!(Searcher::<u8>::new(Vec::new()).table(), &[!0]);
+
+    let pattern = (0..40).map(|x| x % 4).collect::<Vec<_>>();
+    let searcher = Searcher::new(pattern.clone());
+    assert_eq!(searcher.table(), PrefixTable::new(&pattern).as_slice());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_searcher_period() {
+    let period = |pattern: &[u8]| Searcher::new(pattern.to_vec()).smallest_period();
+    let periodic = |pattern: &[u8]| Searcher::new(pattern.to_vec()).is_periodic();
+    assert_eq!(period(b"abcabcab"), 3);
+    assert_eq!(period(b"aaaa"), 1);
+    assert_eq!(period(b"abab"), 2);
+    assert_eq!(period(b"abaab"), 3);
+    assert_eq!(period(b"abcd"), 4);
+    assert_eq!(period(b"a"), 1);
+    assert_eq!(period(b""), 0);
+    assert!(periodic(b"abcabcab"));
+    assert!(periodic(b"abab"));
+    assert!(!periodic(b"abaab"));
+    assert!(!periodic(b"a"));
+    assert!(!periodic(b""));
+
+    // check against the definition
+    let pattern = b"abaababaabaababaababa";
+    for len in 1..pattern.len() + 1 {
+        let pattern = &pattern[..len];
+        let truth = (1..len + 1).find(|&p| (0..len - p).all(|i| pattern[i] == pattern[i + p]));
+        assert_eq!(Some(period(pattern)), truth);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_searcher_from_reader() {
+    let searcher = Searcher::from_reader(&b"string"[..]).unwrap();
+    assert_eq!(searcher.pattern(), b"string");
+    assert_eq!(searcher.find(b"substrinstring"), Some(8));
+    assert_eq!(searcher.find(b"stringstring"), Some(0));
+
+    let searcher = Searcher::from_reader(std::io::empty()).unwrap();
+    assert_eq!(searcher.find(b"xyz"), Some(0));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_searcher_find_all() {
+    let searcher = Searcher::new(b"aa".to_vec());
+    let texts: [&[u8]; 4] = [b"aaaaa", b"abaab", b"a", b""];
+    for text in &texts {
+        assert_eq!(searcher.find_all(text).collect::<Vec<_>>(),
+                   knuth_morris_pratt_all(text, b"aa").collect::<Vec<_>>());
+    }
+    let searcher = Searcher::new(Vec::new());
+    assert_eq!(searcher.find_all(b"ab").collect::<Vec<_>>(), [0, 1, 2]);
+}
+
+#[test]
+fn test_matches_in() {
+    let mut scratch = [0; 5];
+    let texts: [&[u8]; 5] = [b"aaaaa", b"abaab", b"abcabca", b"a", b""];
+    for text in &texts {
+        for pattern in &[&b""[..], b"a", b"aa", b"abca"] {
+            let matches = knuth_morris_pratt_matches_in(text, pattern, &mut scratch).unwrap();
+            assert!(matches.eq(knuth_morris_pratt_all(text, pattern)));
+        }
+    }
+    match knuth_morris_pratt_matches_in(b"abc", b"abcde", &mut scratch) {
+        Err(KmpError::ScratchTooSmall { needed: 6, got: 5 }) => {}
+        _ => panic!("expected KmpError::ScratchTooSmall"),
+    }
+    assert!(knuth_morris_pratt_matches_in(b"abc", b"", &mut []).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_searcher_into_matches() {
+    let texts: [&[u8]; 5] = [b"aaaaa", b"abaab", b"aabaa", b"a", b""];
+    for pattern in &[&b""[..], b"a", b"aa", b"aab"] {
+        for text in &texts {
+            let matches = Searcher::new(pattern.to_vec()).into_matches(text.to_vec());
+            assert_eq!(matches.collect::<Vec<_>>(),
+                       knuth_morris_pratt_all(text, pattern).collect::<Vec<_>>());
+        }
+    }
+
+    let mut matches = Searcher::new(b"ab".to_vec()).into_matches(b"xab".to_vec());
+    assert_eq!(matches.next(), Some(1));
+    assert_eq!(matches.next(), None);
+    assert_eq!(matches.next(), None);
+    let (searcher, text) = matches.into_inner();
+    assert_eq!(searcher.find(&text), Some(1));
+}
+
+#[test]
+fn test_slices() {
+    let find = knuth_morris_pratt_slices;
+    assert_eq!(find(&[b"subs", b"trin", b"string"], b"string"), Some(8));
+    assert_eq!(find(&[b"subs", b"tri", b"", b"ng"], b"string"), Some(3));
+    assert_eq!(find(&[b"ab", b"c"], b"abc"), Some(0));
+    assert_eq!(find(&[b"ab", b"c"], b"abcd"), None);
+    assert_eq!(find(&[b"ab", b"c"], b""), Some(0));
+    assert_eq!(find(&[], b"a"), None);
+}
+
+#[test]
+fn test_longest_prefix_match() {
+    let longest = knuth_morris_pratt_longest_prefix_match;
+    assert_eq!(longest(b"substrinstring", b"string"), (8, 6));
+    assert_eq!(longest(b"substrinstring", b"strinx"), (3, 5));
+    assert_eq!(longest(b"abcab", b"abd"), (0, 2));
+    assert_eq!(longest(b"xyz", b"abc"), (0, 0));
+    assert_eq!(longest(b"xya", b"abc"), (2, 1));
+    assert_eq!(longest(b"ab", b"abc"), (0, 2));
+    assert_eq!(longest(b"ab", b""), (0, 0));
+}
+
+#[test]
+fn test_rev() {
+    assert_eq!(knuth_morris_pratt_rev(b"substrinstring", b"str"), Some(8));
+    assert_eq!(knuth_morris_pratt_rev(b"aaaaa", b"aa"), Some(3));
+    assert_eq!(knuth_morris_pratt_rev(b"xyz", b"a"), None);
+    assert_eq!(knuth_morris_pratt_rev(b"xyz", b""), Some(3));
+    assert_eq!(knuth_morris_pratt_rev(b"xy", b"xyz"), None);
+}
+
+#[test]
+fn test_rev_before() {
+    let text = b"substrinstring";
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"str", 14), Some(8));
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"str", 11), Some(8));
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"str", 10), Some(3));
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"str", 5), None);
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"str", 0), None);
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"str", usize::MAX), Some(8));
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"", 4), Some(4));
+    assert_eq!(knuth_morris_pratt_rev_before(text, b"", 20), Some(14));
+}
+
+#[test]
+fn test_last() {
+    assert_eq!(knuth_morris_pratt_last(b"substrinstring", b"str"), Some(8));
+    assert_eq!(knuth_morris_pratt_last(b"aaaaa", b"aa"), Some(3));
+    assert_eq!(knuth_morris_pratt_last(b"xyz", b"a"), None);
+    assert_eq!(knuth_morris_pratt_last(b"xyz", b""), Some(3));
+    assert_eq!(knuth_morris_pratt_last(b"xy", b"xyz"), None);
+}
+
+#[test]
+fn test_count() {
+    assert_eq!(knuth_morris_pratt_count(b"aaaaa", b"aa"), 2);
+    assert_eq!(knuth_morris_pratt_count(b"abcabc", b"bc"), 2);
+    assert_eq!(knuth_morris_pratt_count(b"abc", b"x"), 0);
+    assert_eq!(knuth_morris_pratt_count(b"abc", b""), "abc".matches("").count());
+    assert_eq!(knuth_morris_pratt_count(b"", b""), 1);
+}
+
+#[test]
+fn test_count_overlapping() {
+    assert_eq!(knuth_morris_pratt_count_overlapping(b"aaaa", b"aa"), 3);
+    assert_eq!(knuth_morris_pratt_count(b"aaaa", b"aa"), 2);
+    assert_eq!(knuth_morris_pratt_count_overlapping(b"abababa", b"aba"), 3);
+    assert_eq!(knuth_morris_pratt_count_overlapping(b"abc", b"x"), 0);
+    assert_eq!(knuth_morris_pratt_count_overlapping(b"abc", b""), 4);
+}
+
+#[test]
+fn test_all_back() {
+    let texts: [&[u8]; 5] = [b"aaaaaaa", b"abaabaaba", b"xyzxyz", b"x", b""];
+    let patterns: [&[u8]; 5] = [b"aa", b"aba", b"xyz", b"q", b""];
+    for text in &texts {
+        for pattern in &patterns {
+            let forward = knuth_morris_pratt_all(text, pattern).collect::<Vec<_>>();
+            let mut back = knuth_morris_pratt_all(text, pattern).rev().collect::<Vec<_>>();
+            back.reverse();
+            assert_eq!(forward, back, "text={:?} pattern={:?}", text, pattern);
+        }
+    }
+
+    // from both ends
+    let mut matches = knuth_morris_pratt_all(b"aaaaaaa", b"aa");
+    assert_eq!(matches.next_back(), Some(4));
+    assert_eq!(matches.next(), Some(0));
+    assert_eq!(matches.next_back(), Some(2));
+    assert_eq!(matches.next(), None);
+    assert_eq!(matches.next_back(), None);
+
+    let mut matches = knuth_morris_pratt_all(b"ab", b"");
+    assert_eq!(matches.next_back(), Some(2));
+    assert_eq!(matches.next(), Some(0));
+    assert_eq!(matches.next_back(), Some(1));
+    assert_eq!(matches.next(), None);
+    assert_eq!(matches.next_back(), None);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_all_back_linear() {
+    use core::cell::Cell;
+
+    // an element that counts the comparisons
+    struct Counted<'a>(u8, &'a Cell<usize>);
+    impl<'a> PartialEq for Counted<'a> {
+        fn eq(&self, other: &Self) -> bool {
+            self.1.set(self.1.get() + 1);
+            self.0 == other.0
+        }
+    }
+
+    let comparisons = Cell::new(0);
+    let text = (0..10_000).map(|_| Counted(b'a', &comparisons)).collect::<Vec<_>>();
+    let pattern = [Counted(b'a', &comparisons), Counted(b'a', &comparisons)];
+    // the pattern overlaps itself, so the matches are found forward
+    assert_eq!(knuth_morris_pratt_all(&text, &pattern).rev().count(), 5000);
+    assert!(comparisons.get() < 2 * text.len(), "{}", comparisons.get());
+
+    let mut matches = knuth_morris_pratt_all(b"aaaaaaa", b"aa");
+    assert_eq!(matches.next_back(), Some(4));
+    assert_eq!(matches.size_hint(), (2, Some(2)));
+    assert_eq!(matches.next(), Some(0));
+    assert_eq!(matches.with_count().len(), 1);
+}
+
+#[test]
+fn test_all_with_count() {
+    let mut matches = knuth_morris_pratt_all(b"abaabaaba", b"aba").with_count();
+    assert_eq!(matches.len(), 3);
+    assert_eq!(matches.next(), Some(0));
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches.next_back(), Some(6));
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches.next(), Some(3));
+    assert_eq!(matches.len(), 0);
+    assert_eq!(matches.next(), None);
+    assert_eq!(matches.len(), 0);
+
+    let mut matches = knuth_morris_pratt_all(b"aaaaa", b"aa");
+    assert_eq!(matches.size_hint(), (0, Some(2)));
+    matches.next();
+    assert_eq!(matches.with_count().len(), 1);
+    assert_eq!(knuth_morris_pratt_all(b"abc", b"").with_count().len(), 4);
+    assert_eq!(knuth_morris_pratt_all(b"abc", b"").size_hint(), (0, Some(4)));
+    assert_eq!(knuth_morris_pratt_all(b"ab", b"abc").with_count().len(), 0);
+}
+
+#[test]
+fn test_overlapping() {
+    let result = knuth_morris_pratt_overlapping(b"aaaa", b"aa").collect::<Vec<_>>();
+    assert_eq!(result, vec![0, 1, 2]);
+    let result = knuth_morris_pratt_overlapping(b"abababa", b"aba").collect::<Vec<_>>();
+    assert_eq!(result, vec![0, 2, 4]);
+    let result = knuth_morris_pratt_overlapping(b"abcab", b"ab").collect::<Vec<_>>();
+    assert_eq!(result, vec![0, 3]);
+    let result = knuth_morris_pratt_overlapping(b"ab", b"").collect::<Vec<_>>();
+    assert_eq!(result, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_overlapping_from() {
+    let from = |start| knuth_morris_pratt_overlapping_matches_from(b"aaaaa", b"aa", start)
+                           .collect::<Vec<_>>();
+    assert_eq!(from(0), [0, 1, 2, 3]);
+    assert_eq!(from(2), [2, 3]);
+    assert_eq!(from(4), Vec::<usize>::new());
+    assert_eq!(from(6), Vec::<usize>::new());
+    assert_eq!(knuth_morris_pratt_overlapping_from(b"abababa", b"aba", 1), Some(2));
+    assert_eq!(knuth_morris_pratt_overlapping_from(b"abababa", b"aba", 5), None);
+    assert_eq!(knuth_morris_pratt_overlapping_from(b"abababa", b"aba", 8), None);
+    assert_eq!(knuth_morris_pratt_overlapping_from(b"abababa", b"", 7), Some(7));
+}
+
+#[test]
+fn test_from() {
+    assert_eq!(knuth_morris_pratt_from(b"abcabc", b"bc", 0), Some(1));
+    assert_eq!(knuth_morris_pratt_from(b"abcabc", b"bc", 1), Some(1));
+    assert_eq!(knuth_morris_pratt_from(b"abcabc", b"bc", 2), Some(4));
+    assert_eq!(knuth_morris_pratt_from(b"abcabc", b"bc", 5), None);
+    assert_eq!(knuth_morris_pratt_from(b"abcabc", b"", 6), Some(6));
+    assert_eq!(knuth_morris_pratt_from(b"abcabc", b"", 7), None);
+    assert_eq!(knuth_morris_pratt_from(b"abcabc", b"bc", usize::MAX), None);
+}
+
+#[test]
+fn test_try() {
+    let text = [0; 64];
+    assert_eq!(knuth_morris_pratt_try(&text, &[0, 1]).ok(), Some(None));
+    assert_eq!(knuth_morris_pratt_try(&text, &text[..STACK_NEXT_SIZE - 1]).ok(), Some(Some(0)));
+    let result = knuth_morris_pratt_try(&text, &text[..STACK_NEXT_SIZE]);
+    if cfg!(feature = "alloc") {
+        assert_eq!(result.ok(), Some(Some(0)));
+    } else {
+        match result {
+            Err(KmpError::PatternTooLong { len: STACK_NEXT_SIZE, max }) => {
+                assert_eq!(max, STACK_NEXT_SIZE - 1);
+            }
+            _ => panic!("expected KmpError::PatternTooLong"),
+        }
+    }
+
+    // zero-sized elements allow slices of any length
+    let all = unsafe {
+        slice::from_raw_parts(core::ptr::NonNull::<()>::dangling().as_ptr(), usize::MAX)
+    };
+    match knuth_morris_pratt_try(&[(); 4], all) {
+        Err(KmpError::PatternTooLong { len: usize::MAX, .. }) => {}
+        _ => panic!("expected KmpError::PatternTooLong"),
+    }
+    // too long for the table to be allocated
+    let len = isize::MAX as usize / mem::size_of::<usize>();
+    match knuth_morris_pratt_try(&[(); 4], &all[..len]) {
+        Err(KmpError::PatternTooLong { len: l, max }) => {
+            assert_eq!(l, len);
+            assert!(max < len);
+        }
+        _ => panic!("expected KmpError::PatternTooLong"),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_error_display() {
+    use alloc::string::ToString;
+
+    let e = KmpError::ScratchTooSmall { needed: 4, got: 3 };
+    assert_eq!(e.to_string(), "scratch of length 3 is too short for the shift table, it needs 4");
+    let e = KmpError::PatternTooLong { len: 40, max: 31 };
+    assert_eq!(e.to_string(), "pattern of length 40 is too long, the maximum is 31");
+}
+
+#[test]
+fn test_try_in() {
+    let mut scratch = [0; 4];
+    assert_eq!(knuth_morris_pratt_try_in(b"abcabc", b"ca", &mut scratch).ok(), Some(Some(2)));
+    assert_eq!(knuth_morris_pratt_try_in(b"ab", b"abc", &mut scratch).ok(), Some(None));
+    assert_eq!(knuth_morris_pratt_try_in(b"", b"", &mut scratch).ok(), Some(Some(0)));
+    match knuth_morris_pratt_try_in(b"abcabc", b"abc", &mut scratch[..3]) {
+        Err(KmpError::ScratchTooSmall { needed: 4, got: 3 }) => {}
+        _ => panic!("expected KmpError::ScratchTooSmall"),
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+#[test]
+#[should_panic]
+fn test_long_pattern_without_alloc() {
+    let text = [0; 64];
+    knuth_morris_pratt(&text, &text[..STACK_NEXT_SIZE]);
+}
+
+#[test]
+fn test_limited() {
+    let text = b"substrinstring";
+    assert_eq!(knuth_morris_pratt_limited(text, b"str", 6), Some(3));
+    assert_eq!(knuth_morris_pratt_limited(text, b"str", 5), None);
+    assert_eq!(knuth_morris_pratt_limited(text, b"string", 14), Some(8));
+    assert_eq!(knuth_morris_pratt_limited(text, b"string", 13), None);
+    assert_eq!(knuth_morris_pratt_limited(text, b"string", usize::MAX), Some(8));
+    assert_eq!(knuth_morris_pratt_limited(text, b"", 0), Some(0));
+}
+
+#[test]
+fn test_range() {
+    let text = b"substrinstring";
+    let range = knuth_morris_pratt_range(text, b"string");
+    assert_eq!(range, Some(8..14));
+    assert_eq!(&text[range.unwrap()], b"string");
+    assert_eq!(knuth_morris_pratt_range(text, b""), Some(0..0));
+    assert_eq!(knuth_morris_pratt_range(text, b"xyz"), None);
+}
+
+#[test]
+fn test_contains() {
+    assert!(knuth_morris_pratt_contains(b"substrinstring", b"string"));
+    assert!(!knuth_morris_pratt_contains(b"substrinstring", b"strung"));
+    assert!(knuth_morris_pratt_contains(b"xyz", b""));
+    assert!(knuth_morris_pratt_contains(b"", b""));
+    assert!(!knuth_morris_pratt_contains(b"xy", b"xyz"));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_prefix_table() {
+    let table = PrefixTable::new(b"abab");
+    assert_eq!(table.as_slice(), &[!0, 0, !0, 0, 2]);
+    assert_eq!(table.len(), 5);
+    assert_eq!(table[0], !0);
+    assert_eq!(PrefixTable::new::<u8>(&[]).as_slice(), &[!0]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_search_with_table() {
+    let pattern = b"string";
+    let table = PrefixTable::new(pattern);
+    assert_eq!(search_with_table(b"substrinstring", pattern, &table), Some(8));
+    assert_eq!(search_with_table(b"substrin", pattern, &table), None);
+    assert_eq!(search_with_table(b"stringy", pattern, &table), Some(0));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_with_table() {
+    // the table of GCAGAGAG, computed by hand
+    let (index, table) = knuth_morris_pratt_with_table(b"GCATCGCAGAGAGTATACAGTACG", b"GCAGAGAG");
+    assert_eq!(index, Some(5));
+    assert_eq!(table, [!0, 0, 0, !0, 1, !0, 1, !0, 1]);
+
+    let (index, table) = knuth_morris_pratt_with_table(b"GCAGAGA", b"GCAGAGAG");
+    assert_eq!(index, None);
+    assert_eq!(table.len(), 9);
+    assert_eq!(knuth_morris_pratt_with_table(b"abc", b""), (Some(0), vec![!0]));
+}
+
+#[test]
+fn test_prepare_kmp_const() {
+    static TABLE: [usize; 9] = prepare_kmp_const(b"GCAGAGAG");
+    const EMPTY: [usize; 1] = prepare_kmp_const(b"");
+    assert_eq!(TABLE, [!0, 0, 0, !0, 1, !0, 1, !0, 1]);
+    assert_eq!(EMPTY, [!0]);
+    assert_eq!(search_with_table(b"GCATCGCAGAGAGTATACAGTACG", b"GCAGAGAG", &TABLE), Some(5));
+    assert_eq!(search_with_table(b"GCATCGCAGAGA", b"GCAGAGAG", &TABLE), None);
+    assert_eq!(search_with_table(b"abc", b"", &EMPTY), Some(0));
+
+    let patterns: [&[u8; 6]; 4] = [b"aaaaaa", b"abaaba", b"ababab", b"string"];
+    for pattern in &patterns {
+        let mut table = [0; 7];
+        prepare_kmp(&pattern[..], &mut table, &mut PartialEq::eq);
+        assert_eq!(prepare_kmp_const::<6, 7>(pattern), table);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_prepare_kmp_const_wrong_len() {
+    let _: [usize; 4] = prepare_kmp_const(b"abcd");
+}
+
+#[cfg(all(feature = "alloc", debug_assertions))]
+#[test]
+#[should_panic]
+fn test_search_with_wrong_table() {
+    let table = PrefixTable::new(b"str");
+    search_with_table(b"substrinstring", b"string", &table);
+}
+
+#[test]
+fn test_ascii_case_insensitive() {
+    let find = knuth_morris_pratt_ascii_case_insensitive;
+    assert_eq!(find(b"Hello World", b"WORLD"), Some(6));
+    assert_eq!(find(b"Hello World", b"hello"), Some(0));
+    assert_eq!(find(b"Hello World", b"WORLDS"), None);
+    assert_eq!(find("ÅÄÖ".as_bytes(), "åäö".as_bytes()), None);
+}
+
+#[test]
+fn test_wildcard() {
+    let find = |text: &str, pattern: &str| {
+        knuth_morris_pratt_wildcard(text.as_bytes(), pattern.as_bytes(), &b'?')
+    };
+    assert_eq!(find("axc", "a?c"), Some(0));
+    assert_eq!(find("aaxcc", "a?c"), Some(1));
+    assert_eq!(find("axxc", "a?c"), None);
+    assert_eq!(find("ab", "a?c"), None);
+    assert_eq!(find("abc", "???"), Some(0));
+    assert_eq!(find("xxaab", "aa?"), Some(2));
+    assert_eq!(find("xxaab", "aab"), Some(2));
+    assert_eq!(find("abc", ""), Some(0));
+    // the wildcard in the text is an ordinary element
+    assert_eq!(find("a?c", "abc"), None);
+}
+
+#[test]
+fn test_classes() {
+    assert_eq!(knuth_morris_pratt_classes(b"axc", &[b"a", b"xy", b"c"]), Some(0));
+    assert_eq!(knuth_morris_pratt_classes(b"ayc", &[b"a", b"xy", b"c"]), Some(0));
+    assert_eq!(knuth_morris_pratt_classes(b"azc", &[b"a", b"xy", b"c"]), None);
+    assert_eq!(knuth_morris_pratt_classes(b"aaxyc", &[b"a", b"xy", b"xy", b"c"]), Some(1));
+    assert_eq!(knuth_morris_pratt_classes(b"ab", &[b"a", b"", b"b"]), None);
+    assert_eq!(knuth_morris_pratt_classes(b"ab", &[b"ab", b"ab", b"ab"]), None);
+    assert_eq!(knuth_morris_pratt_classes::<u8>(b"ab", &[]), Some(0));
+
+    // singleton classes are a plain search
+    let texts = ["", "a", "substrinstring", "aaaaaaaab", "abababcabababab"];
+    let patterns = ["", "a", "aab", "ababc", "string", "abababab"];
+    for text in &texts {
+        for pattern in &patterns {
+            let classes = pattern.as_bytes().iter().map(slice::from_ref).collect::<Vec<_>>();
+            assert_eq!(knuth_morris_pratt_classes(text.as_bytes(), &classes),
+                       knuth_morris_pratt(text.as_bytes(), pattern.as_bytes()));
+        }
+    }
+}
+
+#[test]
+fn test_str() {
+    assert_eq!(knuth_morris_pratt_str("abcαaαβγ", "αβ"), Some(6));
+    assert_eq!(knuth_morris_pratt_str("abcαaαβγ", "βγ"), Some(8));
+    assert_eq!(knuth_morris_pratt_str("abcαaαβγ", "βα"), None);
+    assert_eq!(knuth_morris_pratt_str("αβ", ""), Some(0));
+}
+
+#[test]
+fn test_iter() {
+    assert_eq!(knuth_morris_pratt_iter("substrinstring".bytes(), b"string"), Some(8));
+    assert_eq!(knuth_morris_pratt_iter((0..10).map(|x| x * 2), &[4, 6]), Some(2));
+    assert_eq!(knuth_morris_pratt_iter(0..10, &[4, 6]), None);
+    assert_eq!(knuth_morris_pratt_iter(0..10, &[]), Some(0));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_any() {
+    let text = b"error: warning: note:";
+    assert_eq!(knuth_morris_pratt_any(text, &[b"note", b"warning"]), Some((7, 1)));
+    assert_eq!(knuth_morris_pratt_any(text, &[b"warn", b"warning"]), Some((7, 0)));
+    assert_eq!(knuth_morris_pratt_any(text, &[b"warning", b"warn"]), Some((7, 0)));
+    assert_eq!(knuth_morris_pratt_any(text, &[b"x", b"y"]), None);
+    assert_eq!(knuth_morris_pratt_any(text, &[]), None);
+    assert_eq!(knuth_morris_pratt_any(text, &[b"note", b""]), Some((0, 1)));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_replace() {
+    assert_eq!(knuth_morris_pratt_replace(b"aaaaa", b"aa", b"b"), b"bba");
+    assert_eq!(knuth_morris_pratt_replace(b"abcabc", b"bc", b"xyz"), b"axyzaxyz");
+    assert_eq!(knuth_morris_pratt_replace(b"abc", b"x", b"y"), b"abc");
+    assert_eq!(knuth_morris_pratt_replace(b"abc", b"", b"-"), b"-a-b-c-");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_replace_cow() {
+    let texts = ["", "a", "aaaaa", "abcabc", "substrinstring"];
+    let patterns = ["", "a", "aa", "bc", "x", "string"];
+    for text in &texts {
+        for pattern in &patterns {
+            let (t, p) = (text.as_bytes(), pattern.as_bytes());
+            let result = knuth_morris_pratt_replace_cow(t, p, b"-");
+            assert_eq!(result, knuth_morris_pratt_replace(t, p, b"-"));
+            match result {
+                Cow::Borrowed(b) => {
+                    assert!(!knuth_morris_pratt_contains(t, p));
+                    assert_eq!(b.as_ptr(), t.as_ptr());
+                }
+                Cow::Owned(_) => assert!(knuth_morris_pratt_contains(t, p)),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_split() {
+    let split = |text, pattern| knuth_morris_pratt_split(text, pattern).collect::<Vec<_>>();
+    assert_eq!(split(b"a,b,,c", b","), [&b"a"[..], b"b", b"", b"c"]);
+    assert_eq!(split(b",a,", b","), [&b""[..], b"a", b""]);
+    assert_eq!(split(b"abc", b"x"), [&b"abc"[..]]);
+    assert_eq!(split(b"", b"x"), [&b""[..]]);
+    assert_eq!(split(b"abc", b""), [&b""[..], b"a", b"b", b"c", b""]);
+    assert_eq!(split(b"", b""), [&b""[..], b""]);
+}
+
+#[test]
+fn test_rmatches() {
+    let rmatches = |text, pattern| knuth_morris_pratt_rmatches(text, pattern).collect::<Vec<_>>();
+    assert_eq!(rmatches(b"abcabc", b"bc"), [4, 1]);
+    assert_eq!(rmatches(b"aaa", b"aa"), [1]);
+    assert_eq!(rmatches(b"aaaa", b"aa"), [2, 0]);
+    assert_eq!(rmatches(b"abc", b"x"), Vec::<usize>::new());
+    assert_eq!(rmatches(b"ab", b"abc"), Vec::<usize>::new());
+    assert_eq!(rmatches(b"abc", b""), [3, 2, 1, 0]);
+    assert_eq!(rmatches(b"", b""), [0]);
+}
+
+#[test]
+fn test_overlapping_rmatches() {
+    let rmatches = |text: &[u8], pattern: &[u8]| {
+        knuth_morris_pratt_overlapping_rmatches(text, pattern).collect::<Vec<_>>()
+    };
+    assert_eq!(rmatches(b"aaaa", b"aa"), [2, 1, 0]);
+    assert_eq!(rmatches(b"abababa", b"aba"), [4, 2, 0]);
+    assert_eq!(rmatches(b"abcab", b"ab"), [3, 0]);
+    assert_eq!(rmatches(b"abc", b""), [3, 2, 1, 0]);
+    assert_eq!(rmatches(b"ab", b"abc"), Vec::<usize>::new());
+    assert_eq!(rmatches(b"", b"a"), Vec::<usize>::new());
+}
+
+#[test]
+fn test_rsplit() {
+    let rsplit = |text, pattern| knuth_morris_pratt_rsplit(text, pattern).collect::<Vec<_>>();
+    assert_eq!(rsplit(b"a,b,,c", b","), [&b"c"[..], b"", b"b", b"a"]);
+    assert_eq!(rsplit(b",a,", b","), [&b""[..], b"a", b""]);
+    assert_eq!(rsplit(b"abc", b"x"), [&b"abc"[..]]);
+    assert_eq!(rsplit(b"", b"x"), [&b""[..]]);
+    assert_eq!(rsplit(b"abc", b""), [&b""[..], b"c", b"b", b"a", b""]);
+    assert_eq!(rsplit(b"", b""), [&b""[..], b""]);
+}
+
+#[test]
+fn test_search_builder() {
+    let search = |text| SearchBuilder::new(text, b"aba");
+    assert_eq!(search(b"abababa").all().collect::<Vec<_>>(), [0, 4]);
+    assert_eq!(search(b"abababa").overlapping(true).count(), 3);
+    assert_eq!(search(b"abababa").from(1).first(), Some(2));
+    assert_eq!(search(b"abababa").from(7).first(), None);
+    assert_eq!(search(b"abababa").from(8).first(), None);
+    assert_eq!(search(b"abababa").reverse(true).from(1).all().collect::<Vec<_>>(), [4]);
+    assert_eq!(search(b"ABA").by(|a, b| a.eq_ignore_ascii_case(b)).first(), Some(0));
+    assert_eq!(SearchBuilder::new(b"ab", b"").reverse(true).all().collect::<Vec<_>>(),
+               [2, 1, 0]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_stack_size_boundary() {
+    // the shift table of a pattern of length m has m + 1 entries; check the
+    // patterns that fill the stack table and the first ones that don't fit
+    for len in STACK_NEXT_SIZE - 2..STACK_NEXT_SIZE + 2 {
+        // a periodic pattern, so that the table has nontrivial entries
+        let pattern = (0..len).map(|x| (x % 3 == 2) as u8).collect::<Vec<_>>();
+        // a near miss followed by a match
+        let mut text = pattern.clone();
+        text[len - 1] = 2;
+        text.extend(&pattern);
+        assert_eq!(knuth_morris_pratt(&text, &pattern), Some(len));
+        assert_eq!(knuth_morris_pratt(&pattern, &pattern), Some(0));
+        assert_eq!(knuth_morris_pratt(&pattern[1..], &pattern), None);
+    }
+}
+
+#[test]
+fn test_zero_sized() {
+    // every element is equal to every other
+    assert_eq!(knuth_morris_pratt(&[(); 5], &[(); 3]), Some(0));
+    assert_eq!(knuth_morris_pratt(&[(); 3], &[(); 3]), Some(0));
+    assert_eq!(knuth_morris_pratt(&[(); 2], &[(); 3]), None);
+    assert_eq!(knuth_morris_pratt_rev(&[(); 5], &[(); 3]), Some(2));
+    assert_eq!(knuth_morris_pratt_count(&[(); 7], &[(); 3]), 2);
+    assert_eq!(knuth_morris_pratt_overlapping(&[(); 5], &[(); 3]).collect::<Vec<_>>(),
+               [0, 1, 2]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_zero_sized_long() {
+    // a shift table that does not fit the stack
+    assert_eq!(knuth_morris_pratt(&[(); 100], &[(); 40]), Some(0));
+    assert_eq!(knuth_morris_pratt_rev(&[(); 100], &[(); 40]), Some(60));
+    assert_eq!(knuth_morris_pratt(&[(); 39], &[(); 40]), None);
+}
+
+#[test]
+#[should_panic(expected = "pattern too long")]
+fn test_pattern_too_long() {
+    // zero-sized elements allow slices of any length
+    let all = unsafe {
+        slice::from_raw_parts(core::ptr::NonNull::<()>::dangling().as_ptr(), usize::MAX)
+    };
+    knuth_morris_pratt(all, all);
+}
+
+#[test]
+#[should_panic]
+fn test_pattern_table_too_large() {
+    // the table for this pattern does not fit in memory
+    let all = unsafe {
+        slice::from_raw_parts(core::ptr::NonNull::<()>::dangling().as_ptr(), usize::MAX >> 1)
+    };
+    knuth_morris_pratt(all, all);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_stack_size_boundary_equal_len() {
+    // the stack table fits patterns shorter than STACK_NEXT_SIZE, and a
+    // pattern as long as the text passes the length guard
+    for len in STACK_NEXT_SIZE - 1..STACK_NEXT_SIZE + 2 {
+        let pattern = (0..len).map(|x| (x % 3 == 2) as u8).collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt(&pattern, &pattern), Some(0), "len={}", len);
+        assert_eq!(knuth_morris_pratt_rev(&pattern, &pattern), Some(0), "len={}", len);
+        assert_eq!(knuth_morris_pratt_count(&pattern, &pattern), 1, "len={}", len);
+
+        // mismatch at the first and at the last element
+        for &k in &[0, len - 1] {
+            let mut text = pattern.clone();
+            text[k] = 2;
+            assert_eq!(knuth_morris_pratt(&text, &pattern), None, "len={} k={}", len, k);
+            assert_eq!(knuth_morris_pratt_rev(&text, &pattern), None, "len={} k={}", len, k);
+            assert_eq!(knuth_morris_pratt_count(&text, &pattern), 0, "len={} k={}", len, k);
+        }
+    }
+}
+
+#[cfg(all(feature = "smallvec", feature = "alloc"))]
+#[test]
+fn test_small_size_boundary() {
+    for len in SMALL_NEXT_SIZE - 2..SMALL_NEXT_SIZE + 2 {
+        let pattern = (0..len).map(|x| (x % 3 == 2) as u8).collect::<Vec<_>>();
+        let mut text = pattern.clone();
+        text[len - 1] = 2;
+        text.extend(&pattern);
+        assert_eq!(knuth_morris_pratt(&text, &pattern), Some(len));
+        assert_eq!(knuth_morris_pratt(&pattern[1..], &pattern), None);
+        match NextTable::new(&pattern[..], &mut PartialEq::eq) {
+            NextTable::Small(ref v) => assert_eq!(v.spilled(), len >= SMALL_NEXT_SIZE),
+            _ => panic!("expected a smallvec table"),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_with_stack() {
+    for len in 1..10 {
+        let pattern = (0..len).map(|x| (x % 3 == 2) as u8).collect::<Vec<_>>();
+        let mut text = pattern.clone();
+        text[len - 1] = 2;
+        text.extend(&pattern);
+        assert_eq!(knuth_morris_pratt_with_stack::<4, _>(&text, &pattern), Some(len));
+        assert_eq!(knuth_morris_pratt_with_stack::<1, _>(&text, &pattern), Some(len));
+        assert_eq!(knuth_morris_pratt_with_stack::<0, _>(&text, &pattern), Some(len));
+    }
+    assert_eq!(knuth_morris_pratt_with_stack::<0, u8>(b"", b""), Some(0));
+}
+
+#[test]
+fn test_in() {
+    let mut scratch = [0; 100];
+    for len in 1..10 {
+        let pattern = (0..len).map(|x| (x % 3 == 2) as u8).collect::<Vec<_>>();
+        let mut text = pattern.clone();
+        text[len - 1] = 2;
+        text.extend(&pattern);
+        assert_eq!(knuth_morris_pratt_in(&text, &pattern, &mut scratch), Some(len));
+        assert_eq!(knuth_morris_pratt_in(&text, &pattern, &mut scratch[..len + 1]), Some(len));
+    }
+    assert_eq!(knuth_morris_pratt_in(b"abc", b"", &mut [0]), Some(0));
+}
+
+#[test]
+#[should_panic(expected = "too short")]
+fn test_in_too_short() {
+    knuth_morris_pratt_in(b"abcabc", b"abc", &mut [0; 3]);
+}
+
+#[test]
+fn test_match_indices() {
+    let body = [[1, 2], [3, 4], [5, 6], [3, 4], [7, 0]];
+    let result = knuth_morris_pratt_match_indices(&body, &[[3, 4]]).collect::<Vec<_>>();
+    assert_eq!(result, vec![(1, &body[1..2]), (3, &body[3..4])]);
+    let result = knuth_morris_pratt_match_indices(b"abc", b"").collect::<Vec<_>>();
+    assert_eq!(result, vec![(0, &b""[..]), (1, b""), (2, b""), (3, b"")]);
+}
+
+#[test]
+fn test_match_ranges() {
+    let text = b"abcabcab";
+    let result = knuth_morris_pratt_match_ranges(text, b"cab").collect::<Vec<_>>();
+    assert_eq!(result, vec![2..5, 5..8]);
+    assert!(result.iter().all(|range| &text[range.clone()] == b"cab"));
+    let result = knuth_morris_pratt_match_ranges(b"ab", b"").collect::<Vec<_>>();
+    assert_eq!(result, vec![0..0, 1..1, 2..2]);
+}
+
+#[test]
+fn test_by_checked() {
+    let body = [[1, 2], [3, 4], [5, 6], [3, 4], [5, 10]];
+    let pat = [[7, 0], [12, 3]];
+    let result = knuth_morris_pratt_by_checked(&body, &pat, |a, b| a[0] + a[1] == b[0] + b[1]);
+    assert_eq!(result, Some(3));
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "not reflexive")]
+fn test_by_checked_not_reflexive() {
+    knuth_morris_pratt_by_checked(&[1, 2, 3], &[1, 2], |a, b| a < b);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "not symmetric")]
+fn test_by_checked_not_symmetric() {
+    knuth_morris_pratt_by_checked(&[1, 2, 3], &[1, 2], |a, b| a <= b);
+}
+
+#[test]
+fn test_nth() {
+    assert_eq!(knuth_morris_pratt_nth(b"a,b,c,d", b",", 0), Some(1));
+    assert_eq!(knuth_morris_pratt_nth(b"a,b,c,d", b",", 2), Some(5));
+    assert_eq!(knuth_morris_pratt_nth(b"a,b,c,d", b",", 3), None);
+    assert_eq!(knuth_morris_pratt_nth(b"aaaaa", b"aa", 1), Some(2));
+}
+
+#[test]
+fn test_find_map() {
+    let text = b"len=3:abc,len=x,len=2:de";
+    let mut seen = Vec::new();
+    let value = knuth_morris_pratt_find_map(text, b"len=", |start, text| {
+        seen.push(start);
+        let digit = *text.get(start + 4)?;
+        if digit.is_ascii_digit() && start > 0 { Some(digit - b'0') } else { None }
+    });
+    assert_eq!(value, Some(2));
+    assert_eq!(seen, vec![0, 10, 16]);
+
+    assert_eq!(knuth_morris_pratt_find_map(b"aaaa", b"aa", |start, _| Some(start)), Some(0));
+    assert_eq!(knuth_morris_pratt_find_map(b"aaaa", b"aa", |start, _| {
+        if start > 0 { Some(start) } else { None }
+    }), Some(2));
+    assert_eq!(knuth_morris_pratt_find_map::<_, (), _>(b"abc", b"x", |_, _| unreachable!()),
+               None);
+}
+
+#[test]
+fn test_pattern_is_text() {
+    // equal lengths pass the `pattern.len() > text.len()` guard, and the
+    // match ends at the end of the text
+    fn check<T: Eq + Clone + core::fmt::Debug>(text: &[T]) {
+        let pattern = text.to_vec();
+        assert_eq!(knuth_morris_pratt(text, &pattern), Some(0));
+        assert_eq!(knuth_morris_pratt_rev(text, &pattern), Some(0));
+        assert_eq!(knuth_morris_pratt_last(text, &pattern), Some(0));
+        assert_eq!(knuth_morris_pratt_count(text, &pattern), 1);
+        assert_eq!(knuth_morris_pratt_count_overlapping(text, &pattern), 1);
+        assert_eq!(knuth_morris_pratt_all(text, &pattern).collect::<Vec<_>>(), [0]);
+        assert_eq!(knuth_morris_pratt_all(text, &pattern).rev().collect::<Vec<_>>(), [0]);
+        assert_eq!(knuth_morris_pratt_overlapping(text, &pattern).collect::<Vec<_>>(), [0]);
+        assert_eq!(knuth_morris_pratt_rmatches(text, &pattern).collect::<Vec<_>>(), [0]);
+        assert_eq!(knuth_morris_pratt_overlapping_rmatches(text, &pattern)
+                       .collect::<Vec<_>>(), [0]);
+        assert_eq!(knuth_morris_pratt_from(text, &pattern, 1), None);
+        #[cfg(feature = "alloc")]
+        assert_eq!(Searcher::new(pattern.clone()).find_all(text).collect::<Vec<_>>(), [0]);
+    }
+    check(b"a");
+    check(b"aaaa");
+    check(b"abab");
+    check(b"substring");
+    check(&[7]);
+    check(&[1, 1, 1]);
+    check(&[1, 2, 1, 2, 1]);
+    check(&[-3, 0, i32::MAX, i32::MIN]);
+}
+
+#[test]
+fn test_search_direction() {
+    let texts = ["", "a", "aaa", "substrinstring", "abababcabababab"];
+    let patterns = ["", "a", "aa", "string", "abab"];
+    for text in &texts {
+        for pattern in &patterns {
+            let (t, p) = (text.as_bytes(), pattern.as_bytes());
+            assert_eq!(search(t, p, Direction::Forward), knuth_morris_pratt(t, p));
+            assert_eq!(search(t, p, Direction::Backward), knuth_morris_pratt_rev(t, p));
+        }
+    }
+    assert_eq!(search(&[0.5, 1., 0.5], &[0.5], Direction::Backward), Some(2));
+}
+
+#[test]
+fn test_windows_matching() {
+    let texts = ["", "a", "aaaa", "substrinstring", "abababcabababab"];
+    let patterns = ["", "a", "aa", "string", "abab", "ababc"];
+    for text in &texts {
+        for pattern in &patterns {
+            let (t, p) = (text.as_bytes(), pattern.as_bytes());
+            let mask = knuth_morris_pratt_windows_matching(t, p);
+            let truth = if p.is_empty() {
+                vec![true; t.len() + 1]
+            } else {
+                t.windows(p.len()).map(|w| w == p).collect()
+            };
+            assert_eq!(mask.len(), truth.len());
+            assert_eq!(mask.collect::<Vec<_>>(), truth, "text={:?} pattern={:?}", text, pattern);
+        }
+    }
+
+    let mut mask = knuth_morris_pratt_windows_matching(&[1, 2, 1, 2], &[1, 2]);
+    assert_eq!(mask.next(), Some(true));
+    assert_eq!(mask.len(), 2);
+    assert_eq!(mask.next(), Some(false));
+    assert_eq!(mask.next(), Some(true));
+    assert_eq!(mask.next(), None);
+    assert_eq!(mask.next(), None);
+}
+
+#[test]
+fn test_kmp_iter_ext() {
+    let texts = ["", "a", "substrinstring", "abababcabababab"];
+    let patterns = ["", "a", "string", "ababc", "abababab"];
+    for text in &texts {
+        for pattern in &patterns {
+            let (t, p) = (text.as_bytes(), pattern.as_bytes());
+            assert_eq!(t.iter().cloned().kmp_find(p), knuth_morris_pratt(t, p));
+        }
+    }
+
+    // the rest of the iterator is not consumed
+    let mut iter = b"xabcab".iter();
+    assert_eq!(iter.by_ref().kmp_find(&[&b'a', &b'b']), Some(1));
+    assert_eq!(iter.as_slice(), b"cab");
+}
+
+#[test]
+fn test_bounds() {
+    let texts = ["", "a", "aaaa", "substrinstring", "abababcabababab"];
+    let patterns = ["", "a", "aa", "string", "abab", "x"];
+    for text in &texts {
+        for pattern in &patterns {
+            let (t, p) = (text.as_bytes(), pattern.as_bytes());
+            let truth = knuth_morris_pratt(t, p).map(|first| {
+                (first, knuth_morris_pratt_rev(t, p).unwrap())
+            });
+            assert_eq!(knuth_morris_pratt_bounds(t, p), truth,
+                       "text={:?} pattern={:?}", text, pattern);
+        }
+    }
+    assert_eq!(knuth_morris_pratt_bounds(&[1, 2, 3], &[2, 3]), Some((1, 1)));
+}
+
+#[test]
+fn test_trace() {
+    let texts = ["", "a", "aaaa", "substrinstring", "abababcabababab"];
+    let patterns = ["", "a", "aa", "string", "abab", "ababc"];
+    for text in &texts {
+        for pattern in &patterns {
+            let (t, p) = (text.as_bytes(), pattern.as_bytes());
+            let trace = knuth_morris_pratt_trace(t, p);
+            assert_eq!(trace.len(), t.len());
+            for (index, i) in trace {
+                // the longest prefix of the pattern that is a suffix of the text so far
+                let truth = (0..p.len() + 1).rev()
+                    .find(|&k| k <= index + 1 && t[..index + 1].ends_with(&p[..k]))
+                    .unwrap();
+                assert_eq!(i, truth, "text={:?} pattern={:?} index={}", text, pattern, index);
+            }
+            let ends = knuth_morris_pratt_trace(t, p)
+                .filter(|&(_, i)| i == p.len())
+                .map(|(index, _)| index + 1 - p.len());
+            if !p.is_empty() {
+                assert!(ends.eq(knuth_morris_pratt_overlapping(t, p)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_total_f64() {
+    let nan = f64::NAN;
+    let text = [0.5, nan, 1.0, -0.0, nan, nan, 2.0];
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[nan]), Some(1));
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[nan, nan]), Some(4));
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[nan, 1.0]), Some(1));
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[nan, 2.0]), Some(5));
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[-0.0, nan]), Some(3));
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[0.0]), None);
+    // a NaN with a different payload or sign is a different number
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[-nan]), None);
+    assert_eq!(knuth_morris_pratt_total_f64(&text, &[f64::from_bits(nan.to_bits() + 1)]), None);
+
+    // with `==`, a NaN matches nothing, and the zeros match each other
+    assert_eq!(knuth_morris_pratt_by(&text, &[nan], f64::eq), None);
+    assert_eq!(knuth_morris_pratt_by(&text, &[nan, 2.0], f64::eq), None);
+    assert_eq!(knuth_morris_pratt_by(&text, &[1.0, 0.0], f64::eq), Some(2));
+    assert_eq!(knuth_morris_pratt_rev(&text, &[nan]), None);
+    assert_eq!(knuth_morris_pratt_rev(&text, &[2.0]), Some(6));
+    assert_eq!(knuth_morris_pratt_all(&text, &[nan]).count(), 0);
+}
+
+#[test]
+fn test_prefix() {
+    assert!(knuth_morris_pratt_prefix(b"substring", b"sub"));
+    assert!(knuth_morris_pratt_prefix(b"substring", b""));
+    assert!(!knuth_morris_pratt_prefix(b"substring", b"string"));
+    assert!(!knuth_morris_pratt_prefix(b"sub", b"substring"));
+
+    let body = [[1, 2], [3, 4], [5, 6]];
+    assert!(knuth_morris_pratt_prefix_by(&body, &[[0, 3], [7, 0]],
+                                         |a, b| a[0] + a[1] == b[0] + b[1]));
+}
+
+#[test]
+fn test_at() {
+    assert!(knuth_morris_pratt_at(b"substring", b"str", 3));
+    assert!(!knuth_morris_pratt_at(b"substring", b"str", 2));
+    assert!(!knuth_morris_pratt_at(b"substring", b"ring", 6));
+    assert!(knuth_morris_pratt_at(b"substring", b"ring", 5));
+    assert!(knuth_morris_pratt_at(b"substring", b"", 9));
+    assert!(!knuth_morris_pratt_at(b"substring", b"", 10));
+    assert!(!knuth_morris_pratt_at(b"substring", b"s", usize::MAX));
+
+    let body = [[1, 2], [3, 4], [5, 6]];
+    assert!(knuth_morris_pratt_at_by(&body, &[[0, 7], [7, 4]], 1,
+                                     |a, b| a[0] + a[1] == b[0] + b[1]));
+}
+
+#[test]
+fn test_for_each() {
+    use core::ops::ControlFlow;
+
+    let mut seen = Vec::new();
+    let result = knuth_morris_pratt_for_each(b"abcabcabc", b"bc", |start| {
+        seen.push(start);
+        if start > 3 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    });
+    assert_eq!(result, ControlFlow::Break(()));
+    assert_eq!(seen, vec![1, 4]);
+
+    let result = knuth_morris_pratt_for_each(b"abcabcabc", b"bc", |_| ControlFlow::Break(()));
+    assert_eq!(result, ControlFlow::Break(()));
+    let result = knuth_morris_pratt_for_each::<_, _, ()>(b"abc", b"x", |_| unreachable!());
+    assert_eq!(result, ControlFlow::Continue(()));
 }
@@ -0,0 +1,1188 @@
+//! Iterators of the matches of a pattern.
+
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::iter;
+use core::ops::{ControlFlow, Deref, Range};
+
+use builder::{SearchBuilder, SearchMatches};
+use {feed, knuth_morris_pratt_from, prepare_kmp, scan, KmpError, NextTable, Rev};
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`. Return an iterator of the start offsets of the matches, in order.
+///
+/// The empty pattern matches at every offset from `0` to `text.len()`,
+/// inclusive.
+pub fn knuth_morris_pratt_all<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> Matches<'a, T>
+    where T: PartialEq
+{
+    Matches {
+        state: MatchState::new(text, pattern),
+        back: None,
+        #[cfg(feature = "alloc")]
+        buffer: None,
+    }
+}
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`, like `knuth_morris_pratt_all`, and append the start offsets of the
+/// matches to `out`, in order.
+///
+/// `out` is not cleared first, so that one vector can be reused for the
+/// results of many searches.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_all_into;
+///
+/// let mut out = Vec::new();
+/// knuth_morris_pratt_all_into(b"abcab", b"ab", &mut out);
+/// assert_eq!(out, [0, 3]);
+/// out.clear();
+/// knuth_morris_pratt_all_into(b"aabb", b"ab", &mut out);
+/// assert_eq!(out, [1]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_all_into<T>(text: &[T], pattern: &[T], out: &mut Vec<usize>)
+    where T: PartialEq
+{
+    out.extend(knuth_morris_pratt_all(text, pattern));
+}
+
+/// Count the non-overlapping occurences of `pattern` as a substring of
+/// `text` in each of `buckets` equal-width regions of the text. Return the
+/// counts, one per region, in order.
+///
+/// A match is counted in the region of its start offset, `start * buckets /
+/// text.len()`. The match of the empty pattern at the end of the text is
+/// counted in the last region. If `buckets` is zero, return an empty vector.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_histogram;
+///
+/// let text = b"abab....ab......";
+/// assert_eq!(knuth_morris_pratt_histogram(text, b"ab", 4), [2, 0, 1, 0]);
+/// assert_eq!(knuth_morris_pratt_histogram(text, b"ab", 0), []);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_histogram<T>(text: &[T], pattern: &[T], buckets: usize)
+    -> Vec<usize>
+    where T: PartialEq
+{
+    let mut counts = vec![0; buckets];
+    if buckets == 0 {
+        return counts;
+    }
+    for start in knuth_morris_pratt_all(text, pattern) {
+        // in u128 so that the product does not overflow
+        let bucket = if text.is_empty() {
+            0
+        } else {
+            (start as u128 * buckets as u128 / text.len() as u128) as usize
+        };
+        counts[bucket.min(buckets - 1)] += 1;
+    }
+    counts
+}
+
+/// Count the non-overlapping occurences of each of `patterns` as a
+/// substring of `text`. Return the counts, one per pattern, in order.
+///
+/// The shift table of each pattern is computed once for the text. To
+/// reuse the tables for many texts, use `knuth_morris_pratt_tally_searchers`.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_tally;
+///
+/// let text = b"the cat and the hat";
+/// assert_eq!(knuth_morris_pratt_tally(text, &[b"the", b"at", b"dog"]), [2, 2, 0]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_tally<T>(text: &[T], patterns: &[&[T]]) -> Vec<usize>
+    where T: PartialEq
+{
+    patterns.iter().map(|pattern| knuth_morris_pratt_count(text, pattern)).collect()
+}
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`. Return an iterator of the start offsets of the matches, in order.
+///
+/// The empty pattern matches at every offset from `0` to `text.len()`,
+/// inclusive.
+///
+/// Use the function `equal` for equality comparison.
+pub fn knuth_morris_pratt_all_by<'a, T, FEq>(text: &'a [T], pattern: &'a [T],
+                                             mut equal: FEq)
+    -> MatchesBy<'a, T, FEq>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    MatchesBy {
+        state: MatchState::new_by(text, pattern, &mut equal),
+        equal,
+    }
+}
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`, like `knuth_morris_pratt_all`, using `scratch` for the shift
+/// table. Return an iterator of the start offsets of the matches, in order.
+///
+/// The table needs `pattern.len() + 1` elements, so that one buffer can be
+/// reused for many searches without allocating. If `scratch` is too short,
+/// return `KmpError::ScratchTooSmall` instead.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_matches_in;
+///
+/// let mut scratch = [0; 8];
+/// for text in &[&b"abcab"[..], b"aabb"] {
+///     let matches = knuth_morris_pratt_matches_in(text, b"ab", &mut scratch).unwrap();
+///     assert!(matches.count() > 0);
+/// }
+/// assert!(knuth_morris_pratt_matches_in(b"abc", b"abcdefgh", &mut scratch).is_err());
+/// ```
+pub fn knuth_morris_pratt_matches_in<'a, T>(text: &'a [T], pattern: &'a [T],
+                                            scratch: &'a mut [usize])
+    -> Result<MatchesIn<'a, T>, KmpError>
+    where T: PartialEq
+{
+    if scratch.len() <= pattern.len() {
+        return Err(KmpError::ScratchTooSmall {
+            needed: pattern.len() + 1,
+            got: scratch.len(),
+        });
+    }
+    let next = &mut scratch[..pattern.len() + 1];
+    prepare_kmp(pattern, next, &mut PartialEq::eq);
+    Ok(MatchesIn::new(text, pattern, next))
+}
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`, like `knuth_morris_pratt_all_by`, and check each match again
+/// element by element. Return the start offsets of the matches, in
+/// increasing order and without duplicates.
+///
+/// Use the function `equal` for equality comparison. If it is not an
+/// equivalence relation, the shift table can be wrong, and the search can
+/// report a match where `equal` does not hold for every element; such
+/// matches are removed by the check. Matches can still be missed with such
+/// a function, even if it is reflexive, since the search skips over offsets
+/// that the shift table rules out.
+///
+/// The check costs up to `pattern.len()` comparisons per match.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_all_by;
+/// use knuth_morris_pratt::knuth_morris_pratt_all_robust;
+///
+/// // not transitive: 2 is near 1 and 3, but 1 is not near 3
+/// let near = |a: &i32, b: &i32| (a - b).abs() <= 1;
+/// let text = [2, 1, 2, 0];
+/// let pattern = [3, 2, 0];
+/// // 3 is not near 1
+/// assert_eq!(knuth_morris_pratt_all_by(&text, &pattern, near).collect::<Vec<_>>(), [1]);
+/// assert_eq!(knuth_morris_pratt_all_robust(&text, &pattern, near), []);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn knuth_morris_pratt_all_robust<T, FEq>(text: &[T], pattern: &[T], mut equal: FEq)
+    -> Vec<usize>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    let mut state = MatchState::new_by(text, pattern, &mut equal);
+    let mut starts = Vec::new();
+    while let Some(start) = state.next_match(false, &mut equal) {
+        if pattern.iter().zip(&text[start..]).all(|(p, t)| equal(p, t)) {
+            starts.push(start);
+        }
+    }
+    starts
+}
+
+/// Search for all occurences of `pattern` as a substring of `text`,
+/// including overlapping ones. Return an iterator of the start offsets of the
+/// matches, in order.
+///
+/// The empty pattern matches at every offset from `0` to `text.len()`,
+/// inclusive.
+pub fn knuth_morris_pratt_overlapping<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> OverlapMatches<'a, T>
+    where T: PartialEq
+{
+    OverlapMatches {
+        state: MatchState::new(text, pattern),
+    }
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// starting the search at offset `start`, like `knuth_morris_pratt_from`.
+/// Return the start of the substring as an offset from the start of the
+/// text inside a `Some`. If the pattern is not found, or `start` is past the
+/// end of the text, return `None`.
+///
+/// The match may overlap with a match that started before `start`.
+pub fn knuth_morris_pratt_overlapping_from<T>(text: &[T], pattern: &[T], start: usize)
+    -> Option<usize>
+    where T: PartialEq
+{
+    knuth_morris_pratt_from(text, pattern, start)
+}
+
+/// Search for all occurences of `pattern` as a substring of `text` that
+/// start at or after offset `start`, including overlapping ones. Return an
+/// iterator of the start offsets of the matches, as offsets from the start
+/// of the text, in order.
+///
+/// If `start` is past the end of the text, there are no matches.
+pub fn knuth_morris_pratt_overlapping_matches_from<'a, T>(text: &'a [T], pattern: &'a [T],
+                                                          start: usize)
+    -> SearchMatches<'a, T>
+    where T: PartialEq
+{
+    SearchBuilder::new(text, pattern).overlapping(true).from(start).all()
+}
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`, like `str::match_indices`. Return an iterator of the start offsets
+/// of the matches together with the matched subslices of the text, in order.
+pub fn knuth_morris_pratt_match_indices<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> MatchIndices<'a, T>
+    where T: PartialEq
+{
+    MatchIndices {
+        text,
+        matches: knuth_morris_pratt_all(text, pattern),
+    }
+}
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`. Return an iterator of the ranges of the matches in the text, in
+/// order, so that each match is `&text[range]`.
+pub fn knuth_morris_pratt_match_ranges<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> MatchRanges<'a, T>
+    where T: PartialEq
+{
+    MatchRanges {
+        matches: knuth_morris_pratt_all(text, pattern),
+    }
+}
+
+/// Split `text` into the subslices separated by non-overlapping occurences
+/// of `pattern`, like `str::split`. Return an iterator of the subslices.
+///
+/// If the text starts or ends with the pattern, the first or last subslice
+/// is empty. The empty pattern matches at every offset, so it splits the
+/// text into an empty slice, the individual elements, and an empty slice.
+pub fn knuth_morris_pratt_split<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> Split<'a, T>
+    where T: PartialEq
+{
+    Split {
+        text,
+        matches: knuth_morris_pratt_all(text, pattern),
+        position: 0,
+        finished: false,
+    }
+}
+
+/// Count the non-overlapping occurences of `pattern` as a substring of
+/// `text`.
+///
+/// The empty pattern matches at every offset from `0` to `text.len()`,
+/// inclusive, so it is counted `text.len() + 1` times (like `str::matches`
+/// does for ASCII text).
+pub fn knuth_morris_pratt_count<T>(text: &[T], pattern: &[T]) -> usize
+    where T: PartialEq
+{
+    knuth_morris_pratt_all(text, pattern).count()
+}
+
+/// Count all occurences of `pattern` as a substring of `text`, including
+/// overlapping ones.
+///
+/// The empty pattern is counted `text.len() + 1` times.
+pub fn knuth_morris_pratt_count_overlapping<T>(text: &[T], pattern: &[T]) -> usize
+    where T: PartialEq
+{
+    knuth_morris_pratt_overlapping(text, pattern).count()
+}
+
+/// Count the non-overlapping occurences of `pattern` as a substring of
+/// `text`.
+///
+/// The empty pattern is counted `text.len() + 1` times.
+///
+/// Use the function `equal` for equality comparison.
+pub fn knuth_morris_pratt_count_by<T, FEq>(text: &[T], pattern: &[T], equal: FEq)
+    -> usize
+    where FEq: FnMut(&T, &T) -> bool
+{
+    knuth_morris_pratt_all_by(text, pattern, equal).count()
+}
+
+/// Search for the `n`th (counting from zero) non-overlapping occurence of
+/// `pattern` as a substring of `text`, if any. Return the start of the
+/// substring as an offset from the start of the text inside a `Some`. If
+/// there are not that many matches, return `None`.
+///
+/// The search stops at the `n`th match.
+pub fn knuth_morris_pratt_nth<T>(text: &[T], pattern: &[T], n: usize) -> Option<usize>
+    where T: PartialEq
+{
+    knuth_morris_pratt_all(text, pattern).nth(n)
+}
+
+/// Search for the last occurence of `pattern` as a substring of `text`, if
+/// any, like `knuth_morris_pratt_rev`, but scanning the text forward. Return
+/// the start of the substring as an offset from the start of the text inside
+/// a `Some`. If the pattern is not found, return `None`.
+///
+/// The empty pattern matches at `text.len()`.
+pub fn knuth_morris_pratt_last<T>(text: &[T], pattern: &[T]) -> Option<usize>
+    where T: PartialEq
+{
+    // the last match may overlap the one before it
+    knuth_morris_pratt_overlapping(text, pattern).last()
+}
+
+/// Search for the first and the last occurence of `pattern` as a substring
+/// of `text`, if any, in one forward scan. Return the starts of both as
+/// offsets from the start of the text inside a `Some`, the same as those of
+/// `knuth_morris_pratt` and `knuth_morris_pratt_rev`. If the pattern is not
+/// found, return `None`.
+///
+/// Like with `knuth_morris_pratt_last`, the scan includes overlapping
+/// matches, since the last match may overlap the one before it.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_bounds;
+///
+/// assert_eq!(knuth_morris_pratt_bounds(b"a,b,c", b","), Some((1, 3)));
+/// assert_eq!(knuth_morris_pratt_bounds(b"aaa", b"aa"), Some((0, 1)));
+/// assert_eq!(knuth_morris_pratt_bounds(b"abc", b"x"), None);
+/// ```
+pub fn knuth_morris_pratt_bounds<T>(text: &[T], pattern: &[T]) -> Option<(usize, usize)>
+    where T: PartialEq
+{
+    let mut matches = knuth_morris_pratt_overlapping(text, pattern);
+    let first = matches.next()?;
+    Some((first, matches.last().unwrap_or(first)))
+}
+
+/// Call `f` with the start offset of each non-overlapping occurence of
+/// `pattern` as a substring of `text`, in order, until it returns
+/// `ControlFlow::Break`.
+///
+/// Return the `ControlFlow::Break` value that stopped the search, or
+/// `ControlFlow::Continue(())` if `f` was called for all matches.
+///
+/// ```
+/// use std::ops::ControlFlow;
+/// use knuth_morris_pratt::knuth_morris_pratt_for_each;
+///
+/// // find the first match that is followed by a digit
+/// let text = b"x=a, x=1, x=2";
+/// let result = knuth_morris_pratt_for_each(text, b"x=", |start| {
+///     match text.get(start + 2) {
+///         Some(c) if c.is_ascii_digit() => ControlFlow::Break(start),
+///         _ => ControlFlow::Continue(()),
+///     }
+/// });
+/// assert_eq!(result, ControlFlow::Break(5));
+/// ```
+pub fn knuth_morris_pratt_for_each<T, F, B>(text: &[T], pattern: &[T], mut f: F)
+    -> ControlFlow<B>
+    where T: PartialEq,
+          F: FnMut(usize) -> ControlFlow<B>
+{
+    for start in knuth_morris_pratt_all(text, pattern) {
+        f(start)?;
+    }
+    ControlFlow::Continue(())
+}
+
+/// Call `f` with the start offset of each non-overlapping occurence of
+/// `pattern` as a substring of `text` and the text itself, in order, and
+/// return the first `Some` it returns. If there is none, return `None`.
+///
+/// The search stops at the first match for which `f` returns `Some`.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_find_map;
+///
+/// // read the digit after the first `x=` that is followed by one
+/// let text = b"x=a, x=1, x=2";
+/// let value = knuth_morris_pratt_find_map(text, b"x=", |start, text| {
+///     text.get(start + 2).filter(|c| c.is_ascii_digit()).map(|c| c - b'0')
+/// });
+/// assert_eq!(value, Some(1));
+/// ```
+pub fn knuth_morris_pratt_find_map<T, U, F>(text: &[T], pattern: &[T], mut f: F)
+    -> Option<U>
+    where T: PartialEq,
+          F: FnMut(usize, &[T]) -> Option<U>
+{
+    knuth_morris_pratt_all(text, pattern).find_map(|start| f(start, text))
+}
+
+/// Search for all occurences of `pattern` as a substring of `text`,
+/// including overlapping ones, searching from the end of the text. Return an
+/// iterator of the start offsets of the matches, last to first.
+///
+/// The matches are those of `knuth_morris_pratt_overlapping`, in reverse
+/// order.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_overlapping_rmatches;
+///
+/// let matches = knuth_morris_pratt_overlapping_rmatches(&[1, 1, 1, 2, 1, 1], &[1, 1]);
+/// assert_eq!(matches.collect::<Vec<_>>(), [4, 1, 0]);
+/// ```
+pub fn knuth_morris_pratt_overlapping_rmatches<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> OverlapRMatches<'a, T>
+    where T: PartialEq
+{
+    OverlapRMatches {
+        state: MatchState::new_rev(text, pattern),
+    }
+}
+
+/// Return an iterator of the state of the KMP automaton for `pattern` after
+/// each element of `text`: pairs `(index, i)` of the index of the element in
+/// the text and the length `i` of the prefix of the pattern that is matched
+/// by the text up to and including that element.
+///
+/// `i == pattern.len()` signals a match that ends at `index`, so it starts
+/// at `index + 1 - pattern.len()`. The automaton then continues with the
+/// longest proper border of the pattern, so overlapping matches are
+/// included, like in `knuth_morris_pratt_overlapping`. The empty pattern
+/// is matched, with `i == 0`, after every element.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_trace;
+///
+/// let trace = knuth_morris_pratt_trace(b"abaab", b"aab").collect::<Vec<_>>();
+/// assert_eq!(trace, [(0, 1), (1, 0), (2, 1), (3, 2), (4, 3)]);
+/// ```
+pub fn knuth_morris_pratt_trace<'a, T>(text: &'a [T], pattern: &'a [T]) -> Trace<'a, T>
+    where T: PartialEq
+{
+    Trace {
+        text,
+        pattern,
+        next: NextTable::new(pattern, &mut PartialEq::eq),
+        i: 0,
+        j: 0,
+    }
+}
+
+/// Return an iterator that tells, for each window of `text` of the length
+/// of `pattern`, whether it is equal to the pattern, like
+/// `text.windows(pattern.len()).map(|w| w == pattern)`.
+///
+/// The windows are checked in one pass of the search for overlapping
+/// matches, in **O(n)** time, instead of comparing each window. There are
+/// `text.len() - pattern.len() + 1` windows, or none if the pattern is
+/// longer than the text. The empty pattern matches each of the
+/// `text.len() + 1` empty windows.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_windows_matching;
+///
+/// let mask = knuth_morris_pratt_windows_matching(b"abababc", b"aba").collect::<Vec<_>>();
+/// assert_eq!(mask, [true, false, true, false, false]);
+/// ```
+pub fn knuth_morris_pratt_windows_matching<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> WindowsMatching<'a, T>
+    where T: PartialEq
+{
+    WindowsMatching {
+        matches: knuth_morris_pratt_overlapping(text, pattern),
+        next: None,
+        index: 0,
+        len: (text.len() + 1).saturating_sub(pattern.len()),
+    }
+}
+
+/// Search for all non-overlapping occurences of `pattern` as a substring of
+/// `text`, searching from the end of the text, like `str::rmatch_indices`.
+/// Return an iterator of the start offsets of the matches, last to first.
+///
+/// If the pattern overlaps with itself, the matches can be different from
+/// the matches of `knuth_morris_pratt_all`: `aa` matches `aaa` at offset 0
+/// searching forward, but at offset 1 searching backward.
+///
+/// The empty pattern matches at every offset from `text.len()` to `0`.
+pub fn knuth_morris_pratt_rmatches<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> RMatches<'a, T>
+    where T: PartialEq
+{
+    RMatches {
+        state: MatchState::new_rev(text, pattern),
+    }
+}
+
+/// Split `text` into the subslices separated by non-overlapping occurences
+/// of `pattern`, searching from the end of the text, like `str::rsplit`.
+/// Return an iterator of the subslices, last to first.
+pub fn knuth_morris_pratt_rsplit<'a, T>(text: &'a [T], pattern: &'a [T])
+    -> RSplit<'a, T>
+    where T: PartialEq
+{
+    RSplit {
+        text,
+        matches: knuth_morris_pratt_rmatches(text, pattern),
+        position: text.len(),
+        finished: false,
+    }
+}
+
+// The scan state of the match iterators; `N` is the storage of the shift
+// table, which is borrowed for a `Searcher`
+pub(crate) struct MatchState<'t, 'p, T: 't + 'p, N = NextTable> {
+    text: &'t [T],
+    pattern: &'p [T],
+    // None if the pattern is empty or can not match
+    next: Option<N>,
+    i: usize,
+    j: usize,
+    // search the reversed pattern in the reversed text
+    reverse: bool,
+}
+
+impl<'t, 'p, T> MatchState<'t, 'p, T> {
+    pub(crate) fn new(text: &'t [T], pattern: &'p [T]) -> Self
+        where T: PartialEq
+    {
+        MatchState::new_by(text, pattern, &mut PartialEq::eq)
+    }
+
+    pub(crate) fn new_by<FEq>(text: &'t [T], pattern: &'p [T], equal: &mut FEq)
+        -> Self
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        let next = if pattern.is_empty() || pattern.len() > text.len() {
+            None
+        } else {
+            Some(NextTable::new(pattern, equal))
+        };
+        MatchState {
+            text,
+            pattern,
+            next,
+            i: 0,
+            j: 0,
+            reverse: false,
+        }
+    }
+
+    pub(crate) fn new_rev(text: &'t [T], pattern: &'p [T]) -> Self
+        where T: PartialEq
+    {
+        MatchState::new_rev_by(text, pattern, &mut PartialEq::eq)
+    }
+
+    pub(crate) fn new_rev_by<FEq>(text: &'t [T], pattern: &'p [T], equal: &mut FEq)
+        -> Self
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        let next = if pattern.is_empty() || pattern.len() > text.len() {
+            None
+        } else {
+            Some(NextTable::new(Rev(pattern), equal))
+        };
+        MatchState {
+            text,
+            pattern,
+            next,
+            i: 0,
+            j: 0,
+            reverse: true,
+        }
+    }
+}
+
+impl<'t, 'p, T, N> MatchState<'t, 'p, T, N>
+    where N: Deref<Target = [usize]>
+{
+    // use the shift table `next` that was already computed for `pattern`
+    pub(crate) fn with_table(text: &'t [T], pattern: &'p [T], next: N) -> Self {
+        let next = if pattern.is_empty() || pattern.len() > text.len() {
+            None
+        } else {
+            Some(next)
+        };
+        MatchState {
+            text,
+            pattern,
+            next,
+            i: 0,
+            j: 0,
+            reverse: false,
+        }
+    }
+
+    // Find the next match; if `overlap` is true, the next match may
+    // overlap with this one.
+    pub(crate) fn next_match<FEq>(&mut self, overlap: bool, equal: &mut FEq)
+        -> Option<usize>
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        match self.next {
+            Some(ref next) => {
+                let m = if self.reverse {
+                    scan(Rev(self.text), Rev(self.pattern), next,
+                         &mut self.i, &mut self.j, equal)
+                        .map(|rev_start| self.text.len() - rev_start - self.pattern.len())
+                } else {
+                    scan(self.text, self.pattern, next,
+                         &mut self.i, &mut self.j, equal)
+                };
+                // resume with the longest border of the pattern for
+                // overlapping matches, else from the start of the pattern.
+                self.i = if overlap { next[self.pattern.len()] } else { 0 };
+                m
+            }
+            None if self.pattern.is_empty() && self.j <= self.text.len() => {
+                self.j += 1;
+                if self.reverse {
+                    Some(self.text.len() + 1 - self.j)
+                } else {
+                    Some(self.j - 1)
+                }
+            }
+            None => None,
+        }
+    }
+
+    // Count the remaining non-overlapping matches, without advancing
+    fn count_remaining<FEq>(&self, equal: &mut FEq) -> usize
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        debug_assert!(!self.reverse);
+        match self.next {
+            Some(ref next) => {
+                let (mut i, mut j) = (self.i, self.j);
+                let mut count = 0;
+                while scan(self.text, self.pattern, next, &mut i, &mut j, equal).is_some() {
+                    count += 1;
+                    i = 0;
+                }
+                count
+            }
+            None if self.pattern.is_empty() => (self.text.len() + 1).saturating_sub(self.j),
+            None => 0,
+        }
+    }
+
+    // Return true if the pattern can overlap itself, so that a later
+    // occurence can overlap an earlier match
+    #[cfg(feature = "alloc")]
+    fn self_overlapping(&self) -> bool {
+        match self.next {
+            Some(ref next) => next[self.pattern.len()] != 0,
+            None => false,
+        }
+    }
+
+    // An upper bound of the number of remaining non-overlapping matches
+    fn max_remaining(&self) -> usize {
+        let rest = self.text.len().saturating_sub(self.j);
+        match self.next {
+            Some(_) => rest / self.pattern.len(),
+            None if self.pattern.is_empty() && self.j <= self.text.len() => rest + 1,
+            None => 0,
+        }
+    }
+
+    // Find the last of the remaining non-overlapping matches of a forward
+    // search, and stop the search before it. `back` caches the shift table
+    // of the reversed pattern.
+    fn next_match_back<FEq>(&mut self, back: &mut Option<NextTable>, equal: &mut FEq)
+        -> Option<usize>
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        debug_assert!(!self.reverse);
+        let m = self.pattern.len();
+        let next = match self.next {
+            Some(ref next) => next,
+            None if self.pattern.is_empty() && self.j <= self.text.len() => {
+                let end = self.text.len();
+                if end == 0 {
+                    self.j += 1;
+                } else {
+                    self.text = &self.text[..end - 1];
+                }
+                return Some(end);
+            }
+            None => return None,
+        };
+        // the forward search resumes at `j` with an empty prefix of the
+        // pattern matched, unless it has ended
+        let rest = &self.text[self.j..];
+        let start = if next[m] == 0 {
+            // the pattern can't overlap itself, so the last occurence is the
+            // last match
+            let pattern = self.pattern;
+            let back = back.get_or_insert_with(|| NextTable::new(Rev(pattern), equal));
+            scan(Rev(rest), Rev(self.pattern), back, &mut 0, &mut 0, equal)
+                .map(|rev_start| rest.len() - rev_start - m)
+        } else {
+            // a later occurence can overlap an earlier match, so search
+            // forward for the last match
+            let (mut i, mut j) = (0, 0);
+            let mut last = None;
+            while let Some(start) = scan(rest, self.pattern, next, &mut i, &mut j, equal) {
+                last = Some(start);
+                i = 0;
+            }
+            last
+        };
+        start.map(|start| {
+            let start = self.j + start;
+            self.text = &self.text[..start];
+            start
+        })
+    }
+}
+
+/// An iterator of the start offsets of non-overlapping matches of a pattern.
+///
+/// Created with the function `knuth_morris_pratt_all`.
+///
+/// The matches can also be iterated from the back; they are the same matches
+/// as in the forward direction, unlike for
+/// `knuth_morris_pratt_rmatches`. If the pattern can overlap itself, the
+/// matches can only be found forward: with the `alloc` feature, the first
+/// step from the back searches the rest of the text once, in linear time,
+/// and keeps the matches.
+/// Without it, each step from the back searches the remaining text forward,
+/// so iterating over all matches from the back takes quadratic time in the
+/// worst case.
+///
+/// Its `size_hint` is only a loose bound, from zero matches up to
+/// `text.len() / pattern.len()` matches. Use `with_count` for an iterator
+/// that knows its exact length.
+pub struct Matches<'a, T: 'a> {
+    state: MatchState<'a, 'a, T>,
+    // the shift table of the reversed pattern, for iterating from the back
+    back: Option<NextTable>,
+    // the remaining matches of a pattern that can overlap itself, found on
+    // the first step from the back
+    #[cfg(feature = "alloc")]
+    buffer: Option<VecDeque<usize>>,
+}
+
+impl<'a, T> Matches<'a, T>
+    where T: PartialEq
+{
+    /// Count the remaining matches up front, and return an iterator of them
+    /// that knows its exact length.
+    ///
+    /// This searches the text twice: once to count the matches and once
+    /// while iterating.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::knuth_morris_pratt_all;
+    ///
+    /// let matches = knuth_morris_pratt_all(b"abcabc", b"bc").with_count();
+    /// assert_eq!(matches.len(), 2);
+    /// ```
+    pub fn with_count(self) -> CountedMatches<'a, T> {
+        #[cfg(feature = "alloc")]
+        let len = match self.buffer {
+            Some(ref buffer) => buffer.len(),
+            None => self.state.count_remaining(&mut PartialEq::eq),
+        };
+        #[cfg(not(feature = "alloc"))]
+        let len = self.state.count_remaining(&mut PartialEq::eq);
+        CountedMatches {
+            matches: self,
+            len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Matches<'a, T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(ref mut buffer) = self.buffer {
+                return buffer.pop_front();
+            }
+        }
+        self.state.next_match(false, &mut PartialEq::eq)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(ref buffer) = self.buffer {
+                return (buffer.len(), Some(buffer.len()));
+            }
+        }
+        (0, Some(self.state.max_remaining()))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Matches<'a, T>
+    where T: PartialEq
+{
+    fn next_back(&mut self) -> Option<usize> {
+        #[cfg(feature = "alloc")]
+        {
+            if self.buffer.is_none() && self.state.self_overlapping() {
+                // search forward once, instead of once per step
+                let state = &mut self.state;
+                let buffer = iter::from_fn(|| state.next_match(false, &mut PartialEq::eq));
+                self.buffer = Some(buffer.collect());
+            }
+            if let Some(ref mut buffer) = self.buffer {
+                return buffer.pop_back();
+            }
+        }
+        self.state.next_match_back(&mut self.back, &mut PartialEq::eq)
+    }
+}
+
+/// An iterator of the start offsets of non-overlapping matches of a pattern,
+/// with a known length.
+///
+/// Created with the method `Matches::with_count`.
+pub struct CountedMatches<'a, T: 'a> {
+    matches: Matches<'a, T>,
+    // number of remaining matches
+    len: usize,
+}
+
+impl<'a, T> Iterator for CountedMatches<'a, T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        let m = self.matches.next();
+        if m.is_some() {
+            self.len -= 1;
+        }
+        m
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for CountedMatches<'a, T>
+    where T: PartialEq
+{
+    fn next_back(&mut self) -> Option<usize> {
+        let m = self.matches.next_back();
+        if m.is_some() {
+            self.len -= 1;
+        }
+        m
+    }
+}
+
+impl<'a, T> ExactSizeIterator for CountedMatches<'a, T>
+    where T: PartialEq
+{ }
+
+/// An iterator of the start offsets of non-overlapping matches of a pattern,
+/// with the shift table in a borrowed buffer.
+///
+/// Created with the function `knuth_morris_pratt_matches_in` and the method
+/// `StackSearcher::find_all`.
+pub struct MatchesIn<'a, T: 'a> {
+    state: MatchState<'a, 'a, T, &'a [usize]>,
+}
+
+impl<'a, T> MatchesIn<'a, T> {
+    // use the shift table `next` that was already computed for `pattern`
+    pub(crate) fn new(text: &'a [T], pattern: &'a [T], next: &'a [usize]) -> Self {
+        MatchesIn {
+            state: MatchState::with_table(text, pattern, next),
+        }
+    }
+}
+
+impl<'a, T> Iterator for MatchesIn<'a, T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        self.state.next_match(false, &mut PartialEq::eq)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.state.max_remaining()))
+    }
+}
+
+/// An iterator of the start offsets of non-overlapping matches of a pattern,
+/// using a custom equality.
+///
+/// Created with the function `knuth_morris_pratt_all_by`.
+pub struct MatchesBy<'a, T: 'a, FEq> {
+    state: MatchState<'a, 'a, T>,
+    equal: FEq,
+}
+
+impl<'a, T, FEq> Iterator for MatchesBy<'a, T, FEq>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        self.state.next_match(false, &mut self.equal)
+    }
+}
+
+/// An iterator of the start offsets of all matches of a pattern, including
+/// overlapping ones.
+///
+/// Created with the function `knuth_morris_pratt_overlapping`.
+pub struct OverlapMatches<'a, T: 'a> {
+    state: MatchState<'a, 'a, T>,
+}
+
+impl<'a, T> Iterator for OverlapMatches<'a, T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        self.state.next_match(true, &mut PartialEq::eq)
+    }
+}
+
+/// An iterator of the start offsets of all matches of a pattern, including
+/// overlapping ones, from the end of the text.
+///
+/// Created with the function `knuth_morris_pratt_overlapping_rmatches`.
+pub struct OverlapRMatches<'a, T: 'a> {
+    state: MatchState<'a, 'a, T>,
+}
+
+impl<'a, T> Iterator for OverlapRMatches<'a, T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        self.state.next_match(true, &mut PartialEq::eq)
+    }
+}
+
+/// An iterator of the state of the KMP automaton after each element of a
+/// text.
+///
+/// Created with the function `knuth_morris_pratt_trace`.
+pub struct Trace<'a, T: 'a> {
+    text: &'a [T],
+    pattern: &'a [T],
+    next: NextTable,
+    // matched length of the pattern
+    i: usize,
+    // index of the next element of the text
+    j: usize,
+}
+
+impl<'a, T> Iterator for Trace<'a, T>
+    where T: PartialEq
+{
+    type Item = (usize, usize);
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let elem = self.text.get(self.j)?;
+        if !self.pattern.is_empty() {
+            if self.i == self.pattern.len() {
+                // continue after a match with the longest border
+                self.i = self.next[self.i];
+            }
+            feed(elem, self.pattern, &self.next, &mut self.i, &mut PartialEq::eq);
+        }
+        self.j += 1;
+        Some((self.j - 1, self.i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.text.len() - self.j;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Trace<'a, T>
+    where T: PartialEq
+{ }
+
+/// An iterator of whether each window of a text is equal to a pattern.
+///
+/// Created with the function `knuth_morris_pratt_windows_matching`.
+pub struct WindowsMatching<'a, T: 'a> {
+    matches: OverlapMatches<'a, T>,
+    // the next match at or after `index`, if it was searched for
+    next: Option<usize>,
+    index: usize,
+    len: usize,
+}
+
+impl<'a, T> Iterator for WindowsMatching<'a, T>
+    where T: PartialEq
+{
+    type Item = bool;
+    fn next(&mut self) -> Option<bool> {
+        if self.index == self.len {
+            return None;
+        }
+        match self.next {
+            Some(start) if start >= self.index => {}
+            // no more matches is like a match past the last window
+            _ => self.next = Some(self.matches.next().unwrap_or(self.len)),
+        }
+        let is_match = self.next == Some(self.index);
+        self.index += 1;
+        Some(is_match)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for WindowsMatching<'a, T>
+    where T: PartialEq
+{ }
+
+/// An iterator of the start offsets and subslices of non-overlapping matches
+/// of a pattern.
+///
+/// Created with the function `knuth_morris_pratt_match_indices`.
+pub struct MatchIndices<'a, T: 'a> {
+    text: &'a [T],
+    matches: Matches<'a, T>,
+}
+
+impl<'a, T> Iterator for MatchIndices<'a, T>
+    where T: PartialEq
+{
+    type Item = (usize, &'a [T]);
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = self.matches.state.pattern.len();
+        self.matches.next().map(|start| (start, &self.text[start..start + len]))
+    }
+}
+
+/// An iterator of the ranges of non-overlapping matches of a pattern.
+///
+/// Created with the function `knuth_morris_pratt_match_ranges`.
+pub struct MatchRanges<'a, T: 'a> {
+    matches: Matches<'a, T>,
+}
+
+impl<'a, T> Iterator for MatchRanges<'a, T>
+    where T: PartialEq
+{
+    type Item = Range<usize>;
+    fn next(&mut self) -> Option<Range<usize>> {
+        let len = self.matches.state.pattern.len();
+        self.matches.next().map(|start| start..start + len)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.matches.size_hint()
+    }
+}
+
+/// An iterator of the subslices of a text between matches of a pattern.
+///
+/// Created with the function `knuth_morris_pratt_split`.
+pub struct Split<'a, T: 'a> {
+    text: &'a [T],
+    matches: Matches<'a, T>,
+    // start of the next subslice
+    position: usize,
+    finished: bool,
+}
+
+impl<'a, T> Iterator for Split<'a, T>
+    where T: PartialEq
+{
+    type Item = &'a [T];
+    fn next(&mut self) -> Option<&'a [T]> {
+        if self.finished {
+            return None;
+        }
+        match self.matches.next() {
+            Some(start) => {
+                let piece = &self.text[self.position..start];
+                self.position = start + self.matches.state.pattern.len();
+                Some(piece)
+            }
+            None => {
+                self.finished = true;
+                Some(&self.text[self.position..])
+            }
+        }
+    }
+}
+
+/// An iterator of the start offsets of non-overlapping matches of a pattern,
+/// last to first.
+///
+/// Created with the function `knuth_morris_pratt_rmatches`.
+pub struct RMatches<'a, T: 'a> {
+    state: MatchState<'a, 'a, T>,
+}
+
+impl<'a, T> Iterator for RMatches<'a, T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        self.state.next_match(false, &mut PartialEq::eq)
+    }
+}
+
+/// An iterator of the subslices of a text between matches of a pattern,
+/// last to first.
+///
+/// Created with the function `knuth_morris_pratt_rsplit`.
+pub struct RSplit<'a, T: 'a> {
+    text: &'a [T],
+    matches: RMatches<'a, T>,
+    // end of the next subslice
+    position: usize,
+    finished: bool,
+}
+
+impl<'a, T> Iterator for RSplit<'a, T>
+    where T: PartialEq
+{
+    type Item = &'a [T];
+    fn next(&mut self) -> Option<&'a [T]> {
+        if self.finished {
+            return None;
+        }
+        match self.matches.next() {
+            Some(start) => {
+                let piece = &self.text[start + self.matches.state.pattern.len()..self.position];
+                self.position = start;
+                Some(piece)
+            }
+            None => {
+                self.finished = true;
+                Some(&self.text[..self.position])
+            }
+        }
+    }
+}
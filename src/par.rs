@@ -0,0 +1,44 @@
+//! Parallel search with rayon.
+
+use alloc::vec::Vec;
+
+use rayon::prelude::*;
+
+use {find_with_table, NextTable};
+
+/// Check which of `texts` contain `pattern` as a substring, searching the
+/// texts in parallel. Return a vector with an element for each text, which
+/// is `true` if the pattern is found in it.
+///
+/// The shift table of the pattern is computed once and shared by all the
+/// threads.
+///
+/// Requires the `rayon` feature.
+pub fn knuth_morris_pratt_par_contains<T>(texts: &[&[T]], pattern: &[T]) -> Vec<bool>
+    where T: PartialEq + Sync
+{
+    let next = NextTable::new(pattern, &mut PartialEq::eq);
+    texts.par_iter()
+         .map(|text| find_with_table(text, pattern, &next, &mut PartialEq::eq).is_some())
+         .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_contains() {
+        let texts = [&b"substrinstring"[..], b"strin", b"", b"string"];
+        assert_eq!(knuth_morris_pratt_par_contains(&texts, b"string"),
+                   vec![true, false, false, true]);
+        assert_eq!(knuth_morris_pratt_par_contains(&texts, b""),
+                   vec![true, true, true, true]);
+
+        let long_pattern = (0..100).collect::<Vec<_>>();
+        let long_text = (0..200).collect::<Vec<_>>();
+        let texts = [&long_text[..], &long_text[1..], &long_pattern[..99]];
+        assert_eq!(knuth_morris_pratt_par_contains(&texts, &long_pattern),
+                   vec![true, false, false]);
+    }
+}
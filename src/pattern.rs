@@ -0,0 +1,147 @@
+//! Substring search in `str` using Knuth-Morris-Pratt.
+
+#[cfg(feature = "pattern")]
+use core::str::pattern::{Pattern, SearchStep, Searcher};
+
+use knuth_morris_pratt;
+use matches::MatchState;
+
+/// Search for the first occurence of `needle` as a substring of `haystack`,
+/// like `str::find`. Return the byte offset of the match inside a `Some`.
+/// If the pattern is not found, return `None`.
+///
+/// The offset is always at a `char` boundary of the haystack.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_str;
+///
+/// assert_eq!(knuth_morris_pratt_str("abcαaαβγ", "αβ"), Some(6));
+/// ```
+pub fn knuth_morris_pratt_str(haystack: &str, needle: &str) -> Option<usize> {
+    // a match of valid UTF-8 in valid UTF-8 always starts at a char boundary
+    let start = knuth_morris_pratt(haystack.as_bytes(), needle.as_bytes());
+    if let Some(start) = start {
+        debug_assert!(haystack.is_char_boundary(start));
+    }
+    start
+}
+
+/// A string pattern that is searched for using Knuth-Morris-Pratt.
+///
+/// With the crate feature `pattern` (requires a nightly compiler), it
+/// implements the `str` `Pattern` trait, so that it can be used with
+/// `str::find`, `str::match_indices` and the other `str` methods that take a
+/// pattern.
+///
+/// ```
+/// use knuth_morris_pratt::KmpPattern;
+///
+/// let needle = KmpPattern("αβ");
+/// assert_eq!(needle.find("abcαaαβγ"), Some(6));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct KmpPattern<'p>(pub &'p str);
+
+impl<'p> KmpPattern<'p> {
+    /// Search for the first occurence of the pattern in `haystack`, like
+    /// `str::find`. Return the byte offset of the match inside a `Some`.
+    /// If the pattern is not found, return `None`.
+    pub fn find(self, haystack: &str) -> Option<usize> {
+        Iterator::next(&mut self.match_indices(haystack)).map(|(i, _)| i)
+    }
+
+    /// Return an iterator of the non-overlapping matches of the pattern in
+    /// `haystack` and their byte offsets, like `str::match_indices`.
+    pub fn match_indices<'h>(self, haystack: &'h str) -> KmpSearcher<'h, 'p> {
+        KmpSearcher {
+            haystack,
+            needle: self.0,
+            state: MatchState::new(haystack.as_bytes(), self.0.as_bytes()),
+            #[cfg(feature = "pattern")]
+            position: 0,
+            #[cfg(feature = "pattern")]
+            pending: None,
+        }
+    }
+}
+
+/// An iterator of the matches of a `KmpPattern` in a string, and their byte
+/// offsets.
+///
+/// With the crate feature `pattern`, it is also the `str` `Searcher` of the
+/// pattern.
+pub struct KmpSearcher<'h, 'p> {
+    haystack: &'h str,
+    needle: &'p str,
+    state: MatchState<'h, 'p, u8>,
+    // end of the last search step
+    #[cfg(feature = "pattern")]
+    position: usize,
+    // a match that was found but not yet reported as a search step
+    #[cfg(feature = "pattern")]
+    pending: Option<usize>,
+}
+
+impl<'h, 'p> KmpSearcher<'h, 'p> {
+    // find the next match; matches of the empty needle are only reported at
+    // char boundaries, like for `str`
+    fn next_match_start(&mut self) -> Option<usize> {
+        while let Some(start) = self.state.next_match(false, &mut PartialEq::eq) {
+            if self.haystack.is_char_boundary(start) {
+                return Some(start);
+            }
+        }
+        None
+    }
+}
+
+impl<'h, 'p> Iterator for KmpSearcher<'h, 'p> {
+    type Item = (usize, &'h str);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match_start().map(|start| {
+            let end = start + self.needle.len();
+            (start, &self.haystack[start..end])
+        })
+    }
+}
+
+#[cfg(feature = "pattern")]
+impl<'p> Pattern for KmpPattern<'p> {
+    type Searcher<'h> = KmpSearcher<'h, 'p>;
+
+    fn into_searcher(self, haystack: &str) -> KmpSearcher<'_, 'p> {
+        self.match_indices(haystack)
+    }
+}
+
+#[cfg(feature = "pattern")]
+unsafe impl<'h, 'p> Searcher<'h> for KmpSearcher<'h, 'p> {
+    fn haystack(&self) -> &'h str {
+        self.haystack
+    }
+
+    fn next(&mut self) -> SearchStep {
+        let start = match self.pending.take() {
+            Some(start) => start,
+            None => match self.next_match_start() {
+                Some(start) => start,
+                None if self.position < self.haystack.len() => {
+                    let position = self.position;
+                    self.position = self.haystack.len();
+                    return SearchStep::Reject(position, self.haystack.len());
+                }
+                None => return SearchStep::Done,
+            }
+        };
+        if self.position < start {
+            // reject the text between the previous step and the match
+            self.pending = Some(start);
+            let position = self.position;
+            self.position = start;
+            SearchStep::Reject(position, start)
+        } else {
+            self.position = start + self.needle.len();
+            SearchStep::Match(start, self.position)
+        }
+    }
+}
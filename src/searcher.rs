@@ -0,0 +1,468 @@
+//! A reusable searcher.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+#[cfg(feature = "memchr")]
+use memchr::memchr;
+
+use matches::MatchState;
+#[cfg(feature = "memchr")]
+use feed;
+use {find_with_table, max_prefix_border, scan, NextTable};
+
+/// A reusable searcher for one pattern.
+///
+/// The searcher owns the pattern and computes its KMP shift table once, up
+/// front, so that repeated searches only cost **O(n)** in the length of the
+/// text.
+///
+/// Two searchers are equal if their patterns and their shift tables are
+/// equal; searchers for the same pattern created with different equality
+/// functions can have different tables. Equal patterns hash the same. The
+/// alternate `Debug` format, `{:#?}`, also shows the table.
+///
+/// Requires the `alloc` feature.
+#[derive(Clone)]
+pub struct Searcher<T> {
+    pub(crate) pattern: Vec<T>,
+    pub(crate) next: NextTable,
+}
+
+impl<T> Searcher<T> {
+    /// Create a new searcher for `pattern`.
+    pub fn new(pattern: Vec<T>) -> Self
+        where T: PartialEq
+    {
+        Searcher::new_by(pattern, PartialEq::eq)
+    }
+
+    /// Create a new searcher for `pattern`.
+    ///
+    /// Use the function `equal` for equality comparison when computing the
+    /// shift table. Search with `find_by` and the same function: the other
+    /// search methods compare the elements with `==`.
+    pub fn new_by<FEq>(pattern: Vec<T>, mut equal: FEq) -> Self
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        let next = NextTable::new(&pattern[..], &mut equal);
+        Searcher {
+            pattern,
+            next,
+        }
+    }
+
+    /// Create a new searcher for `pattern`.
+    ///
+    /// Compare the keys `key_fn(a) == key_fn(b)` of the elements when
+    /// computing the shift table, for example a cheap projection of a large
+    /// element. Search with `find_by_key` and the same key function.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::Searcher;
+    ///
+    /// let searcher = Searcher::new_by_key(vec![(1, "a"), (2, "b")], |x| x.0);
+    /// let text = [(0, "x"), (1, "y"), (2, "z")];
+    /// assert_eq!(searcher.find_by_key(&text, |x| x.0), Some(1));
+    /// ```
+    pub fn new_by_key<K, F>(pattern: Vec<T>, key_fn: F) -> Self
+        where F: Fn(&T) -> K,
+              K: PartialEq
+    {
+        Searcher::new_by(pattern, |a, b| key_fn(a) == key_fn(b))
+    }
+
+    /// Return the pattern of the searcher.
+    pub fn pattern(&self) -> &[T] {
+        &self.pattern
+    }
+
+    /// Return the KMP shift table of the pattern.
+    ///
+    /// The table has `pattern.len() + 1` entries. After a mismatch at
+    /// position `i` of the pattern, the search continues by comparing
+    /// position `table[i]` of the pattern with the same element of the text.
+    /// The sentinel value `!0` means that the search continues with the next
+    /// element of the text instead; it is always the entry at position 0.
+    /// The last entry is the length of the longest proper prefix of the
+    /// pattern that is also a suffix of it.
+    ///
+    /// For a searcher created with `new`, this is the same table as
+    /// `PrefixTable::new(pattern)`.
+    pub fn table(&self) -> &[usize] {
+        &self.next[..self.pattern.len() + 1]
+    }
+
+    /// Return the smallest period of the pattern: the smallest `p > 0` such
+    /// that `pattern[i] == pattern[i + p]` for all valid `i`. A pattern that
+    /// does not repeat has the period `pattern.len()`, and the empty pattern
+    /// has the period `0`.
+    ///
+    /// Most entries of the shift table are optimized to skip comparisons
+    /// that are known to fail, but the last entry is the length of the
+    /// longest border of the pattern, which gives the period.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::Searcher;
+    ///
+    /// assert_eq!(Searcher::new(b"abcabcab".to_vec()).smallest_period(), 3);
+    /// assert_eq!(Searcher::new(b"abcd".to_vec()).smallest_period(), 4);
+    /// ```
+    pub fn smallest_period(&self) -> usize {
+        let len = self.pattern.len();
+        if len == 0 {
+            0
+        } else {
+            len - self.next[len]
+        }
+    }
+
+    /// Return `true` if the pattern is periodic: if it repeats its smallest
+    /// period at least twice, so that the period is at most half its length.
+    pub fn is_periodic(&self) -> bool {
+        !self.pattern.is_empty() && 2 * self.smallest_period() <= self.pattern.len()
+    }
+
+    /// Return a heuristic, from `0.0` up to below `1.0`, of how much the
+    /// pattern makes a naive search backtrack: the length of the longest
+    /// border of any prefix of the pattern, relative to its length.
+    ///
+    /// KMP never backtracks, so this is not a risk for the searcher itself,
+    /// but it tells how much the shift table saves: the higher the risk, the
+    /// more a naive search can backtrack, up to about `text.len() *
+    /// pattern.len()` comparisons for a pattern like `aa…ab`. At zero, the
+    /// first element of the pattern does not occur again in it, and a naive
+    /// search is also linear.
+    ///
+    /// Compares the elements with `==`.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::Searcher;
+    ///
+    /// assert_eq!(Searcher::new(b"string".to_vec()).pathological_risk(), 0.);
+    /// assert!(Searcher::new(b"aaaaaaab".to_vec()).pathological_risk() > 0.5);
+    /// ```
+    pub fn pathological_risk(&self) -> f64
+        where T: PartialEq
+    {
+        if self.pattern.is_empty() {
+            return 0.;
+        }
+        max_prefix_border(&self.pattern, &mut PartialEq::eq) as f64 / self.pattern.len() as f64
+    }
+
+    /// Search for the first occurence of the pattern as a substring of
+    /// `text`, if any. Return the start of the substring as an offset from
+    /// the start of the text inside a `Some`. If the pattern is not found,
+    /// return `None`.
+    ///
+    /// Compares the elements with `==`, so a searcher created with `new_by`
+    /// or `new_by_key` must be searched with `find_by` or `find_by_key`
+    /// instead.
+    pub fn find(&self, text: &[T]) -> Option<usize>
+        where T: PartialEq
+    {
+        self.find_by(text, PartialEq::eq)
+    }
+
+    /// Search for the first occurence of the pattern as a substring of
+    /// `text`, if any. Return the start of the substring as an offset from
+    /// the start of the text inside a `Some`. If the pattern is not found,
+    /// return `None`.
+    ///
+    /// Use the function `equal` for equality comparison. It must agree with
+    /// the equality used to create the searcher, when comparing elements of
+    /// the pattern with each other.
+    pub fn find_by<FEq>(&self, text: &[T], mut equal: FEq) -> Option<usize>
+        where FEq: FnMut(&T, &T) -> bool
+    {
+        find_with_table(text, &self.pattern, &self.next, &mut equal)
+    }
+
+    /// Search for the first occurence of the pattern as a substring of
+    /// `text`, if any. Return the start of the substring as an offset from
+    /// the start of the text inside a `Some`. If the pattern is not found,
+    /// return `None`.
+    ///
+    /// Compare the keys `key_fn(a) == key_fn(b)` of the elements. The key
+    /// function must agree with the one used to create the searcher.
+    pub fn find_by_key<K, F>(&self, text: &[T], key_fn: F) -> Option<usize>
+        where F: Fn(&T) -> K,
+              K: PartialEq
+    {
+        self.find_by(text, |a, b| key_fn(a) == key_fn(b))
+    }
+
+    /// Count the non-overlapping occurences of the pattern as a substring of
+    /// `text`.
+    ///
+    /// Compares the elements with `==`, like `find`.
+    pub fn count(&self, text: &[T]) -> usize
+        where T: PartialEq
+    {
+        self.find_all(text).count()
+    }
+
+    /// Search for all non-overlapping occurences of the pattern as a
+    /// substring of `text`. Return an iterator of the start offsets of the
+    /// matches, in order.
+    ///
+    /// The iterator borrows the shift table of the searcher, so it is not
+    /// computed again. Compares the elements with `==`, like `find`.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::Searcher;
+    ///
+    /// let searcher = Searcher::new(b"ab".to_vec());
+    /// for text in &[&b"abcab"[..], b"aabb"] {
+    ///     for start in searcher.find_all(text) {
+    ///         assert_eq!(&text[start..start + 2], b"ab");
+    ///     }
+    /// }
+    /// ```
+    pub fn find_all<'a>(&'a self, text: &'a [T]) -> SearcherMatches<'a, T>
+        where T: PartialEq
+    {
+        SearcherMatches {
+            state: MatchState::with_table(text, &self.pattern, &self.next),
+        }
+    }
+
+    /// Search for all non-overlapping occurences of the pattern as a
+    /// substring of the owned `text`. Return an iterator of the start offsets
+    /// of the matches, in order.
+    ///
+    /// The iterator owns the searcher and the text, so it has no lifetime
+    /// and can for example be sent to another thread. Compares the elements
+    /// with `==`, like `find`.
+    ///
+    /// ```
+    /// use std::thread;
+    /// use knuth_morris_pratt::Searcher;
+    ///
+    /// let searcher = Searcher::new(b"ab".to_vec());
+    /// let matches = searcher.into_matches(b"abcab".to_vec());
+    /// let starts = thread::spawn(move || matches.collect::<Vec<_>>()).join().unwrap();
+    /// assert_eq!(starts, [0, 3]);
+    /// ```
+    pub fn into_matches(self, text: Vec<T>) -> OwnedMatches<T>
+        where T: PartialEq
+    {
+        OwnedMatches {
+            searcher: self,
+            text,
+            i: 0,
+            j: 0,
+        }
+    }
+}
+
+impl<T> PartialEq for Searcher<T>
+    where T: PartialEq
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern && self.table() == other.table()
+    }
+}
+
+impl<T> Eq for Searcher<T>
+    where T: Eq
+{ }
+
+impl<T> Hash for Searcher<T>
+    where T: Hash
+{
+    fn hash<H>(&self, state: &mut H)
+        where H: Hasher
+    {
+        self.pattern.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for Searcher<T>
+    where T: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let alternate = f.alternate();
+        let mut debug = f.debug_struct("Searcher");
+        debug.field("pattern", &self.pattern);
+        if alternate {
+            debug.field("table", &self.table());
+        }
+        debug.finish()
+    }
+}
+
+#[cfg(feature = "memchr")]
+impl Searcher<u8> {
+    /// Search for all non-overlapping occurences of the pattern as a
+    /// substring of `text`, like `find_all`. Return an iterator of the start
+    /// offsets of the matches, in order.
+    ///
+    /// When no part of the pattern is matched, the search skips ahead to the
+    /// next occurence of the first byte of the pattern using `memchr`, like
+    /// `knuth_morris_pratt_bytes`. Compares the bytes with `==`, like `find`.
+    ///
+    /// Requires the `memchr` feature.
+    ///
+    /// ```
+    /// use knuth_morris_pratt::Searcher;
+    ///
+    /// let searcher = Searcher::new(b"ab".to_vec());
+    /// let starts = searcher.find_iter_bytes(b"abcab").collect::<Vec<_>>();
+    /// assert_eq!(starts, [0, 3]);
+    /// ```
+    pub fn find_iter_bytes<'t>(&'t self, text: &'t [u8]) -> BytesMatches<'t> {
+        BytesMatches {
+            text,
+            searcher: self,
+            i: 0,
+            j: 0,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Searcher<u8> {
+    /// Create a new searcher for the pattern read from `reader`, to the end
+    /// of the stream.
+    ///
+    /// Requires the `std` feature.
+    pub fn from_reader<R>(mut reader: R) -> io::Result<Self>
+        where R: Read
+    {
+        let mut pattern = Vec::new();
+        reader.read_to_end(&mut pattern)?;
+        Ok(Searcher::new(pattern))
+    }
+}
+
+/// Count the non-overlapping occurences of the pattern of each of
+/// `searchers` as a substring of `text`, like `knuth_morris_pratt_tally`.
+/// Return the counts, one per searcher, in order.
+///
+/// The searchers keep their shift tables, so they can be reused for many
+/// texts.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// use knuth_morris_pratt::{knuth_morris_pratt_tally_searchers, Searcher};
+///
+/// let keywords = [&b"the"[..], b"at", b"dog"];
+/// let searchers = keywords.iter().map(|k| Searcher::new(k.to_vec())).collect::<Vec<_>>();
+/// assert_eq!(knuth_morris_pratt_tally_searchers(b"the cat and the hat", &searchers), [2, 2, 0]);
+/// assert_eq!(knuth_morris_pratt_tally_searchers(b"the dog", &searchers), [1, 0, 1]);
+/// ```
+pub fn knuth_morris_pratt_tally_searchers<T>(text: &[T], searchers: &[Searcher<T>])
+    -> Vec<usize>
+    where T: PartialEq
+{
+    searchers.iter().map(|searcher| searcher.count(text)).collect()
+}
+
+/// An iterator of the start offsets of non-overlapping matches of the
+/// pattern of a `Searcher`.
+///
+/// Created with the method `Searcher::find_all`.
+pub struct SearcherMatches<'a, T: 'a> {
+    state: MatchState<'a, 'a, T, &'a [usize]>,
+}
+
+impl<'a, T> Iterator for SearcherMatches<'a, T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        self.state.next_match(false, &mut PartialEq::eq)
+    }
+}
+
+/// An iterator of the start offsets of non-overlapping matches of the
+/// pattern of a `Searcher`, that owns the searcher and the text.
+///
+/// Created with the method `Searcher::into_matches`.
+pub struct OwnedMatches<T> {
+    searcher: Searcher<T>,
+    text: Vec<T>,
+    i: usize,
+    j: usize,
+}
+
+impl<T> OwnedMatches<T> {
+    /// Return the searcher and the text.
+    pub fn into_inner(self) -> (Searcher<T>, Vec<T>) {
+        (self.searcher, self.text)
+    }
+}
+
+impl<T> Iterator for OwnedMatches<T>
+    where T: PartialEq
+{
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        let pattern = &self.searcher.pattern[..];
+        if pattern.is_empty() {
+            // the empty pattern matches at every offset
+            if self.j > self.text.len() {
+                return None;
+            }
+            self.j += 1;
+            return Some(self.j - 1);
+        }
+        let m = scan(&self.text[..], pattern, &self.searcher.next,
+                     &mut self.i, &mut self.j, &mut PartialEq::eq);
+        self.i = 0;
+        m
+    }
+}
+
+/// An iterator of the start offsets of non-overlapping matches of the
+/// pattern of a `Searcher<u8>`, skipping ahead with `memchr`.
+///
+/// Created with the method `Searcher::find_iter_bytes`.
+#[cfg(feature = "memchr")]
+pub struct BytesMatches<'t> {
+    text: &'t [u8],
+    searcher: &'t Searcher<u8>,
+    i: usize,
+    j: usize,
+}
+
+#[cfg(feature = "memchr")]
+impl<'t> Iterator for BytesMatches<'t> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        let pattern = &self.searcher.pattern[..];
+        if pattern.is_empty() {
+            // the empty pattern matches at every offset
+            if self.j > self.text.len() {
+                return None;
+            }
+            self.j += 1;
+            return Some(self.j - 1);
+        }
+        while self.j < self.text.len() {
+            if self.i == 0 {
+                match memchr(pattern[0], &self.text[self.j..]) {
+                    Some(skip) => self.j += skip,
+                    None => {
+                        self.j = self.text.len();
+                        return None;
+                    }
+                }
+            }
+            let matched = feed(&self.text[self.j], pattern, &self.searcher.next,
+                               &mut self.i, &mut PartialEq::eq);
+            self.j += 1;
+            if matched {
+                self.i = 0;
+                return Some(self.j - pattern.len());
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,124 @@
+//! Serialization of shift tables with serde.
+//!
+//! A table is serialized as the sequence of its entries, and a `Searcher`
+//! as the pair of its pattern and its table. Deserialization checks the
+//! invariants of the table, so that a corrupt table is an error instead of
+//! a panic during the search.
+//!
+//! It does not check that a searcher's table belongs to its pattern, since
+//! a searcher created with `Searcher::new_by` has the table of another
+//! equality. A valid table for another pattern gives wrong matches.
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use {NextTable, PrefixTable, Searcher};
+
+// check that `next` is a valid shift table for a pattern of length `len`
+fn check_table(next: &[usize], len: usize) -> Result<(), &'static str> {
+    if next.len() != len + 1 {
+        return Err("shift table length does not match the pattern");
+    }
+    if next[0] != !0 {
+        return Err("shift table does not start with the sentinel");
+    }
+    // entries point back into the pattern
+    if next.iter().enumerate().skip(1).any(|(i, &n)| n != !0 && n >= i) {
+        return Err("shift table entry out of range");
+    }
+    Ok(())
+}
+
+fn invalid<E: Error>(msg: impl Display) -> E {
+    E::custom(format_args!("invalid shift table: {}", msg))
+}
+
+impl Serialize for PrefixTable {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        self.next.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PrefixTable {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        let next = Vec::<usize>::deserialize(deserializer)?;
+        if next.is_empty() {
+            return Err(invalid("empty table"));
+        }
+        check_table(&next, next.len() - 1).map_err(invalid)?;
+        Ok(PrefixTable { next })
+    }
+}
+
+impl<T> Serialize for Searcher<T>
+    where T: Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        (self.pattern(), self.table()).serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Searcher<T>
+    where T: Deserialize<'de>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        let (pattern, table) = <(Vec<T>, Vec<usize>)>::deserialize(deserializer)?;
+        check_table(&table, pattern.len()).map_err(invalid)?;
+        let mut next = NextTable::for_len(pattern.len());
+        next[..table.len()].copy_from_slice(&table);
+        Ok(Searcher { pattern, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use super::*;
+    use STACK_NEXT_SIZE;
+
+    #[test]
+    fn test_prefix_table_round_trip() {
+        let table = PrefixTable::new(b"GCAGAGAG");
+        let json = serde_json::to_string(&table).unwrap();
+        assert_eq!(serde_json::from_str::<PrefixTable>(&json).unwrap(), table);
+    }
+
+    #[test]
+    fn test_searcher_round_trip() {
+        for &len in &[0, 5, STACK_NEXT_SIZE - 1, STACK_NEXT_SIZE, 100] {
+            let pattern = (0..len).map(|x| x as i32 % 3).collect::<Vec<_>>();
+            let searcher = Searcher::new(pattern.clone());
+            let json = serde_json::to_string(&searcher).unwrap();
+            let searcher = serde_json::from_str::<Searcher<i32>>(&json).unwrap();
+            let mut text = vec![7; 10];
+            text.extend(&pattern);
+            assert_eq!(searcher.find(&text), Some(if len == 0 { 0 } else { 10 }));
+        }
+    }
+
+    #[test]
+    fn test_reject_corrupt() {
+        // the sentinel, which depends on the width of usize
+        let s = !0usize;
+        assert!(serde_json::from_str::<PrefixTable>("[]").is_err());
+        assert!(serde_json::from_str::<PrefixTable>("[0, 0]").is_err());
+        assert!(serde_json::from_str::<PrefixTable>(&format!("[{}, 1]", s)).is_err());
+        assert!(serde_json::from_str::<Searcher<u8>>(&format!("[[1, 2], [{}, 0]]", s)).is_err());
+        assert!(serde_json::from_str::<Searcher<u8>>(&format!("[[1, 2], [{}, 0, 0]]", s)).is_ok());
+        // a valid table, but not the one of the pattern
+        let json = format!("[[1, 2], [{}, {}, 1]]", s, s);
+        assert!(serde_json::from_str::<Searcher<u8>>(&json).is_ok());
+    }
+}
@@ -0,0 +1,149 @@
+//! A reusable searcher that does not allocate.
+
+use core::fmt;
+
+use matches::MatchesIn;
+use {find_with_table, prepare_kmp, KmpError};
+
+/// A reusable searcher for one pattern, with its shift table in an array
+/// of `N` elements.
+///
+/// Like `Searcher`, the searcher computes the shift table of the pattern
+/// once, up front, but it keeps the table inline and borrows the pattern,
+/// so it never allocates and is available without the `alloc` feature.
+///
+/// The table needs `pattern.len() + 1` elements, so the capacity `N` must be
+/// greater than the length of the pattern.
+///
+/// ```
+/// use knuth_morris_pratt::StackSearcher;
+///
+/// let searcher = StackSearcher::<_, 8>::new(b"abc").unwrap();
+/// assert_eq!(searcher.find(b"xxabc"), Some(2));
+/// assert_eq!(searcher.find_all(b"abcabc").collect::<Vec<_>>(), [0, 3]);
+/// assert!(StackSearcher::<_, 8>::new(b"abcdefgh").is_err());
+/// ```
+pub struct StackSearcher<'p, T: 'p, const N: usize> {
+    pattern: &'p [T],
+    next: [usize; N],
+}
+
+impl<'p, T, const N: usize> StackSearcher<'p, T, N> {
+    /// Create a new searcher for `pattern`.
+    ///
+    /// Return `KmpError::ScratchTooSmall` if `N` is less than
+    /// `pattern.len() + 1`.
+    pub fn new(pattern: &'p [T]) -> Result<Self, KmpError>
+        where T: PartialEq
+    {
+        if N <= pattern.len() {
+            return Err(KmpError::ScratchTooSmall {
+                needed: pattern.len() + 1,
+                got: N,
+            });
+        }
+        let mut next = [0; N];
+        prepare_kmp(pattern, &mut next[..pattern.len() + 1], &mut PartialEq::eq);
+        Ok(StackSearcher {
+            pattern,
+            next,
+        })
+    }
+
+    /// Return the pattern of the searcher.
+    pub fn pattern(&self) -> &'p [T] {
+        self.pattern
+    }
+
+    /// Return the KMP shift table of the pattern, like `Searcher::table`.
+    pub fn table(&self) -> &[usize] {
+        &self.next[..self.pattern.len() + 1]
+    }
+
+    /// Search for the first occurence of the pattern as a substring of
+    /// `text`, if any. Return the start of the substring as an offset from
+    /// the start of the text inside a `Some`. If the pattern is not found,
+    /// return `None`.
+    pub fn find(&self, text: &[T]) -> Option<usize>
+        where T: PartialEq
+    {
+        find_with_table(text, self.pattern, &self.next, &mut PartialEq::eq)
+    }
+
+    /// Search for all non-overlapping occurences of the pattern as a
+    /// substring of `text`. Return an iterator of the start offsets of the
+    /// matches, in order.
+    pub fn find_all<'a>(&'a self, text: &'a [T]) -> MatchesIn<'a, T>
+        where T: PartialEq
+    {
+        MatchesIn::new(text, self.pattern, self.table())
+    }
+
+    /// Count the non-overlapping occurences of the pattern as a substring
+    /// of `text`.
+    ///
+    /// The empty pattern is counted `text.len() + 1` times.
+    pub fn count(&self, text: &[T]) -> usize
+        where T: PartialEq
+    {
+        self.find_all(text).count()
+    }
+}
+
+impl<'p, T, const N: usize> Clone for StackSearcher<'p, T, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'p, T, const N: usize> Copy for StackSearcher<'p, T, N> { }
+
+impl<'p, T, const N: usize> fmt::Debug for StackSearcher<'p, T, N>
+    where T: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StackSearcher")
+         .field("pattern", &self.pattern)
+         .field("table", &self.table())
+         .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use {knuth_morris_pratt, knuth_morris_pratt_all};
+
+    #[test]
+    fn test_stack_searcher() {
+        let texts = ["", "a", "aaaa", "substrinstring", "abababcabababab"];
+        let patterns = ["", "a", "aa", "string", "abab", "ababc"];
+        for text in &texts {
+            for pattern in &patterns {
+                let (t, p) = (text.as_bytes(), pattern.as_bytes());
+                let searcher = StackSearcher::<_, 8>::new(p).unwrap();
+                assert_eq!(searcher.find(t), knuth_morris_pratt(t, p));
+                assert!(searcher.find_all(t).eq(knuth_morris_pratt_all(t, p)));
+                assert_eq!(searcher.count(t), knuth_morris_pratt_all(t, p).count());
+                #[cfg(feature = "alloc")]
+                assert_eq!(searcher.table(), &::PrefixTable::new(p)[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stack_searcher_capacity() {
+        // the table needs exactly one more element than the pattern
+        let pattern = b"abcab";
+        let searcher = StackSearcher::<_, 6>::new(pattern).unwrap();
+        assert_eq!(searcher.find(b"abcabcab"), Some(0));
+        assert_eq!(searcher.find_all(b"abcabcab").collect::<Vec<_>>(), [0]);
+        match StackSearcher::<_, 5>::new(pattern) {
+            Err(KmpError::ScratchTooSmall { needed: 6, got: 5 }) => {}
+            _ => panic!("expected KmpError::ScratchTooSmall"),
+        }
+        assert!(StackSearcher::<u8, 1>::new(b"").is_ok());
+        assert!(StackSearcher::<u8, 0>::new(b"").is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Instrumented search, for studying the behavior of the algorithm.
+
+use NextTable;
+
+/// Counts of the work done by `knuth_morris_pratt_instrumented`.
+///
+/// Both preparing the shift table and scanning the text are included.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// The number of element equality comparisons.
+    pub comparisons: usize,
+    /// The number of times a mismatch was resolved by following the shift
+    /// table back to a shorter prefix of the pattern.
+    pub lookups: usize,
+}
+
+/// Search for the first occurence of `pattern` as a substring of `text`,
+/// like `knuth_morris_pratt`, and count the comparisons and shift table
+/// lookups it takes.
+///
+/// The comparisons are at most `2 * (text.len() + pattern.len())`, while a
+/// naive search can take `text.len() * pattern.len()` comparisons, for
+/// example on a periodic text like `aaaa…a` and pattern `aa…ab`.
+///
+/// This is slower than `knuth_morris_pratt` and only meant for analysis.
+///
+/// ```
+/// use knuth_morris_pratt::knuth_morris_pratt_instrumented;
+///
+/// let (index, stats) = knuth_morris_pratt_instrumented(b"aaaaaaab", b"aaab");
+/// assert_eq!(index, Some(4));
+/// assert!(stats.comparisons <= 2 * (8 + 4));
+/// ```
+pub fn knuth_morris_pratt_instrumented<T>(text: &[T], pattern: &[T])
+    -> (Option<usize>, SearchStats)
+    where T: PartialEq
+{
+    let mut comparisons = 0;
+    let mut lookups = 0;
+    let index = {
+        let mut equal = |a: &T, b: &T| {
+            comparisons += 1;
+            a == b
+        };
+        find(text, pattern, &mut lookups, &mut equal)
+    };
+    (index, SearchStats { comparisons, lookups })
+}
+
+// The same steps as `prepare_kmp` and `scan`, counting the lookups
+fn find<T, FEq>(text: &[T], pattern: &[T], lookups: &mut usize, equal: &mut FEq)
+    -> Option<usize>
+    where FEq: FnMut(&T, &T) -> bool
+{
+    if pattern.is_empty() {
+        return Some(0);
+    } else if pattern.len() > text.len() {
+        return None;
+    }
+
+    let mut next = NextTable::for_len(pattern.len());
+    let mut i = 0;
+    let mut j = !0;
+    next[0] = !0;
+    while i < pattern.len() {
+        while let Some(&next_j) = next.get(j) {
+            if equal(&pattern[i], &pattern[j]) {
+                break;
+            }
+            j = next_j;
+            *lookups += 1;
+        }
+        i += 1;
+        j = j.wrapping_add(1);
+        if i != pattern.len() && equal(&pattern[i], &pattern[j]) {
+            next[i] = next[j];
+        } else {
+            next[i] = j;
+        }
+    }
+
+    let mut i = 0;
+    for (j, elem) in text.iter().enumerate() {
+        while let Some(&next_i) = next.get(i) {
+            if equal(&pattern[i], elem) {
+                break;
+            }
+            i = next_i;
+            *lookups += 1;
+        }
+        i = i.wrapping_add(1);
+        if i >= pattern.len() {
+            return Some(j + 1 - pattern.len());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use knuth_morris_pratt;
+
+    #[test]
+    fn test_instrumented() {
+        let texts = ["", "a", "abc", "aaaaaaaaab", "abababcabababab", "xyzabababcxyz"];
+        let patterns = ["", "a", "ab", "aab", "ababc", "abababab", "xyzz"];
+        for text in &texts {
+            for pattern in &patterns {
+                let (index, stats) = knuth_morris_pratt_instrumented(text.as_bytes(),
+                                                                     pattern.as_bytes());
+                assert_eq!(index, knuth_morris_pratt(text.as_bytes(), pattern.as_bytes()));
+                assert!(stats.comparisons <= 2 * (text.len() + pattern.len()),
+                        "{:?} for text={:?} pattern={:?}", stats, text, pattern);
+                assert!(stats.lookups <= stats.comparisons);
+            }
+        }
+    }
+
+    #[test]
+    fn test_instrumented_periodic() {
+        let mut text = [b'a'; 1000];
+        text[999] = b'b';
+        let pattern = b"aaaaaaab";
+        let (index, stats) = knuth_morris_pratt_instrumented(&text, pattern);
+        assert_eq!(index, Some(992));
+        // linear, where a naive search takes about 8000 comparisons
+        assert!(stats.comparisons <= 2 * (text.len() + pattern.len()));
+        assert!(stats.lookups > 0);
+
+        let (_, stats) = knuth_morris_pratt_instrumented(b"abc", b"");
+        assert_eq!(stats, SearchStats::default());
+    }
+
+    // the table of the pattern needs an allocation
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_instrumented_worst_case() {
+        // a naive search compares about 1000 elements at each of the 9000
+        // offsets
+        let text = [b'a'; 10_000];
+        let mut pattern = [b'a'; 1001];
+        pattern[1000] = b'b';
+        let (index, stats) = knuth_morris_pratt_instrumented(&text, &pattern);
+        assert_eq!(index, None);
+        assert!(stats.comparisons < 3 * text.len(), "{:?}", stats);
+    }
+}
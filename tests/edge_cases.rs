@@ -0,0 +1,263 @@
+//! The empty text and the empty pattern, checked against `str` for every
+//! public function.
+//!
+//! Each test covers the four combinations of an empty or nonempty text with
+//! an empty or nonempty pattern.
+
+extern crate knuth_morris_pratt;
+
+use knuth_morris_pratt::knuth_morris_pratt;
+use knuth_morris_pratt::knuth_morris_pratt_all;
+use knuth_morris_pratt::knuth_morris_pratt_all_by;
+#[cfg(feature = "alloc")]
+use knuth_morris_pratt::knuth_morris_pratt_all_into;
+#[cfg(feature = "alloc")]
+use knuth_morris_pratt::knuth_morris_pratt_any;
+use knuth_morris_pratt::knuth_morris_pratt_ascii_case_insensitive;
+use knuth_morris_pratt::knuth_morris_pratt_at;
+use knuth_morris_pratt::knuth_morris_pratt_bounds;
+use knuth_morris_pratt::knuth_morris_pratt_by;
+use knuth_morris_pratt::knuth_morris_pratt_by_checked;
+use knuth_morris_pratt::knuth_morris_pratt_classes;
+#[cfg(feature = "memchr")]
+use knuth_morris_pratt::knuth_morris_pratt_bytes;
+#[cfg(feature = "memchr")]
+use knuth_morris_pratt::knuth_morris_pratt_bytes_ascii_ci;
+use knuth_morris_pratt::knuth_morris_pratt_contains;
+use knuth_morris_pratt::knuth_morris_pratt_count;
+use knuth_morris_pratt::knuth_morris_pratt_count_by;
+use knuth_morris_pratt::knuth_morris_pratt_count_overlapping;
+use knuth_morris_pratt::knuth_morris_pratt_find_map;
+use knuth_morris_pratt::knuth_morris_pratt_for_each;
+use knuth_morris_pratt::knuth_morris_pratt_from;
+use knuth_morris_pratt::knuth_morris_pratt_haystack;
+use knuth_morris_pratt::knuth_morris_pratt_in;
+use knuth_morris_pratt::knuth_morris_pratt_instrumented;
+use knuth_morris_pratt::knuth_morris_pratt_iter;
+use knuth_morris_pratt::knuth_morris_pratt_last;
+use knuth_morris_pratt::knuth_morris_pratt_limited;
+use knuth_morris_pratt::knuth_morris_pratt_match_indices;
+use knuth_morris_pratt::knuth_morris_pratt_match_ranges;
+use knuth_morris_pratt::knuth_morris_pratt_matches_in;
+use knuth_morris_pratt::knuth_morris_pratt_nth;
+use knuth_morris_pratt::knuth_morris_pratt_overlapping;
+use knuth_morris_pratt::knuth_morris_pratt_overlapping_from;
+#[cfg(feature = "rayon")]
+use knuth_morris_pratt::knuth_morris_pratt_par_contains;
+use knuth_morris_pratt::knuth_morris_pratt_prefix;
+use knuth_morris_pratt::knuth_morris_pratt_range;
+#[cfg(feature = "std")]
+use knuth_morris_pratt::knuth_morris_pratt_reader;
+#[cfg(feature = "alloc")]
+use knuth_morris_pratt::knuth_morris_pratt_replace;
+#[cfg(feature = "std")]
+use knuth_morris_pratt::knuth_morris_pratt_replace_stream;
+use knuth_morris_pratt::knuth_morris_pratt_rev;
+use knuth_morris_pratt::knuth_morris_pratt_rev_before;
+use knuth_morris_pratt::knuth_morris_pratt_rev_by;
+use knuth_morris_pratt::knuth_morris_pratt_rmatches;
+use knuth_morris_pratt::knuth_morris_pratt_rsplit;
+use knuth_morris_pratt::knuth_morris_pratt_slices;
+use knuth_morris_pratt::knuth_morris_pratt_split;
+use knuth_morris_pratt::knuth_morris_pratt_str;
+use knuth_morris_pratt::knuth_morris_pratt_try;
+use knuth_morris_pratt::knuth_morris_pratt_try_in;
+use knuth_morris_pratt::knuth_morris_pratt_wildcard;
+use knuth_morris_pratt::knuth_morris_pratt_with_stack;
+use knuth_morris_pratt::search;
+#[cfg(feature = "alloc")]
+use knuth_morris_pratt::search_with_table;
+use knuth_morris_pratt::Direction;
+#[cfg(feature = "alloc")]
+use knuth_morris_pratt::PrefixTable;
+use knuth_morris_pratt::SearchBuilder;
+#[cfg(feature = "alloc")]
+use knuth_morris_pratt::Searcher;
+
+use std::ops::ControlFlow;
+
+const TEXTS: [&str; 2] = ["", "ab"];
+const PATTERNS: [&str; 2] = ["", "b"];
+
+// Call `f` with each combination of text and pattern
+fn each_case<F>(mut f: F)
+    where F: FnMut(&str, &str)
+{
+    for text in &TEXTS {
+        for pattern in &PATTERNS {
+            f(text, pattern);
+        }
+    }
+}
+
+fn starts(text: &str, pattern: &str) -> Vec<usize> {
+    text.match_indices(pattern).map(|(start, _)| start).collect()
+}
+
+#[test]
+fn test_find() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        let truth = text.find(pattern);
+        assert_eq!(knuth_morris_pratt(t, p), truth);
+        assert_eq!(knuth_morris_pratt_by(t, p, u8::eq), truth);
+        assert_eq!(knuth_morris_pratt_by_checked(t, p, u8::eq), truth);
+        assert_eq!(knuth_morris_pratt_str(text, pattern), truth);
+        assert_eq!(knuth_morris_pratt_from(t, p, 0), truth);
+        assert_eq!(knuth_morris_pratt_limited(t, p, t.len()), truth);
+        assert_eq!(knuth_morris_pratt_with_stack::<4, _>(t, p), truth);
+        assert_eq!(knuth_morris_pratt_in(t, p, &mut [0; 4]), truth);
+        assert_eq!(knuth_morris_pratt_try_in(t, p, &mut [0; 4]).unwrap(), truth);
+        assert_eq!(knuth_morris_pratt_try(t, p).unwrap(), truth);
+        assert_eq!(knuth_morris_pratt_ascii_case_insensitive(t, p), truth);
+        assert_eq!(knuth_morris_pratt_wildcard(t, p, &b'?'), truth);
+        let classes = p.iter().map(std::slice::from_ref).collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt_classes(t, &classes), truth);
+        assert_eq!(knuth_morris_pratt_iter(t.iter().cloned(), p), truth);
+        assert_eq!(knuth_morris_pratt_slices(&[t], p), truth);
+        assert_eq!(knuth_morris_pratt_haystack(t, p), truth);
+        assert_eq!(knuth_morris_pratt_instrumented(t, p).0, truth);
+        assert_eq!(knuth_morris_pratt_range(t, p), truth.map(|s| s..s + p.len()));
+        #[cfg(feature = "alloc")]
+        {
+            assert_eq!(knuth_morris_pratt_any(t, &[p]), truth.map(|s| (s, 0)));
+            assert_eq!(search_with_table(t, p, &PrefixTable::new(p)), truth);
+        }
+        assert_eq!(search(t, p, Direction::Forward), truth);
+        assert_eq!(knuth_morris_pratt_bounds(t, p).map(|(first, _)| first), truth);
+        #[cfg(feature = "alloc")]
+        assert_eq!(Searcher::new(p.to_vec()).find(t), truth);
+        assert_eq!(SearchBuilder::new(t, p).first(), truth);
+        #[cfg(feature = "std")]
+        assert_eq!(knuth_morris_pratt_reader(t, p).unwrap(), truth.map(|s| s as u64));
+        #[cfg(feature = "memchr")]
+        {
+            assert_eq!(knuth_morris_pratt_bytes(t, p), truth);
+            assert_eq!(knuth_morris_pratt_bytes_ascii_ci(t, p), truth);
+        }
+    });
+}
+
+#[test]
+fn test_from_end() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        let truth = text[text.len()..].find(pattern).map(|s| s + text.len());
+        assert_eq!(knuth_morris_pratt_from(t, p, t.len()), truth);
+        assert_eq!(knuth_morris_pratt_overlapping_from(t, p, t.len()), truth);
+        assert_eq!(SearchBuilder::new(t, p).from(t.len()).first(), truth);
+    });
+}
+
+#[test]
+fn test_contains() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        assert_eq!(knuth_morris_pratt_contains(t, p), text.contains(pattern));
+        assert_eq!(knuth_morris_pratt_prefix(t, p), text.starts_with(pattern));
+        assert_eq!(knuth_morris_pratt_at(t, p, 0), text.starts_with(pattern));
+        #[cfg(feature = "rayon")]
+        assert_eq!(knuth_morris_pratt_par_contains(&[t], p), [text.contains(pattern)]);
+    });
+}
+
+#[test]
+fn test_rfind() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        let truth = text.rfind(pattern);
+        assert_eq!(knuth_morris_pratt_rev(t, p), truth);
+        assert_eq!(knuth_morris_pratt_rev_by(t, p, u8::eq), truth);
+        assert_eq!(knuth_morris_pratt_rev_before(t, p, t.len()), truth);
+        assert_eq!(knuth_morris_pratt_last(t, p), truth);
+        assert_eq!(search(t, p, Direction::Backward), truth);
+        assert_eq!(knuth_morris_pratt_bounds(t, p).map(|(_, last)| last), truth);
+        assert_eq!(SearchBuilder::new(t, p).reverse(true).first(), truth);
+    });
+}
+
+#[test]
+fn test_matches() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        let truth = starts(text, pattern);
+        let mut rtruth = truth.clone();
+        rtruth.reverse();
+        assert_eq!(knuth_morris_pratt_all(t, p).collect::<Vec<_>>(), truth);
+        assert_eq!(knuth_morris_pratt_all(t, p).rev().collect::<Vec<_>>(), rtruth);
+        assert_eq!(knuth_morris_pratt_all(t, p).with_count().len(), truth.len());
+        assert_eq!(knuth_morris_pratt_all_by(t, p, u8::eq).collect::<Vec<_>>(), truth);
+        assert_eq!(knuth_morris_pratt_overlapping(t, p).collect::<Vec<_>>(), truth);
+        assert_eq!(knuth_morris_pratt_matches_in(t, p, &mut [0; 4]).unwrap()
+                       .collect::<Vec<_>>(), truth);
+        #[cfg(feature = "alloc")]
+        {
+            let mut out = Vec::new();
+            knuth_morris_pratt_all_into(t, p, &mut out);
+            assert_eq!(out, truth);
+        }
+        let mut out = Vec::new();
+        let _ = knuth_morris_pratt_for_each(t, p, |start| {
+            out.push(start);
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(out, truth);
+        #[cfg(feature = "alloc")]
+        assert_eq!(Searcher::new(p.to_vec()).find_all(t).collect::<Vec<_>>(), truth);
+        assert_eq!(SearchBuilder::new(t, p).all().collect::<Vec<_>>(), truth);
+        assert_eq!(SearchBuilder::new(t, p).overlapping(true).all().collect::<Vec<_>>(), truth);
+        assert_eq!(knuth_morris_pratt_nth(t, p, 0), truth.first().cloned());
+        assert_eq!(knuth_morris_pratt_find_map(t, p, |s, _| Some(s)), truth.first().cloned());
+        assert_eq!(knuth_morris_pratt_nth(t, p, 1), truth.get(1).cloned());
+
+        let rstarts = text.rmatch_indices(pattern).map(|(start, _)| start).collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt_rmatches(t, p).collect::<Vec<_>>(), rstarts);
+
+        let indices = text.match_indices(pattern)
+                          .map(|(start, s)| (start, s.as_bytes()))
+                          .collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt_match_indices(t, p).collect::<Vec<_>>(), indices);
+        let ranges = truth.iter().map(|&s| s..s + p.len()).collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt_match_ranges(t, p).collect::<Vec<_>>(), ranges);
+    });
+}
+
+#[test]
+fn test_count() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        let truth = text.matches(pattern).count();
+        assert_eq!(knuth_morris_pratt_count(t, p), truth);
+        assert_eq!(knuth_morris_pratt_count_by(t, p, u8::eq), truth);
+        assert_eq!(knuth_morris_pratt_count_overlapping(t, p), truth);
+        assert_eq!(SearchBuilder::new(t, p).count(), truth);
+    });
+}
+
+#[test]
+fn test_split() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        let truth = text.split(pattern).map(str::as_bytes).collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt_split(t, p).collect::<Vec<_>>(), truth);
+        let truth = text.rsplit(pattern).map(str::as_bytes).collect::<Vec<_>>();
+        assert_eq!(knuth_morris_pratt_rsplit(t, p).collect::<Vec<_>>(), truth);
+    });
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_replace() {
+    each_case(|text, pattern| {
+        let (t, p) = (text.as_bytes(), pattern.as_bytes());
+        let truth = text.replace(pattern, "-");
+        assert_eq!(knuth_morris_pratt_replace(t, p, b"-"), truth.as_bytes());
+        #[cfg(feature = "std")]
+        {
+            let mut out = Vec::new();
+            let count = knuth_morris_pratt_replace_stream(t, &mut out, p, b"-").unwrap();
+            assert_eq!(out, truth.as_bytes());
+            assert_eq!(count, text.matches(pattern).count());
+        }
+    });
+}
@@ -0,0 +1,89 @@
+//! Check that searching for short patterns does not allocate.
+//!
+//! The shift table of a pattern shorter than 32 elements is kept on the
+//! stack. A counting global allocator, installed only in this test binary,
+//! checks that no allocation happens during the search.
+
+extern crate knuth_morris_pratt;
+
+use knuth_morris_pratt::knuth_morris_pratt;
+use knuth_morris_pratt::knuth_morris_pratt_all;
+use knuth_morris_pratt::knuth_morris_pratt_count;
+use knuth_morris_pratt::knuth_morris_pratt_rev;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// the stack table size of the crate, without the smallvec feature
+const STACK_NEXT_SIZE: usize = 32;
+
+thread_local! {
+    // counted per thread, so that other tests running in parallel don't
+    // interfere
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // the thread local is gone while the thread shuts down
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// Return the result of `f` and the number of allocations it made
+fn count_allocations<F, R>(f: F) -> (R, usize)
+    where F: FnOnce() -> R
+{
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_short_pattern_no_alloc() {
+    let mut text = vec![b'a'; 1000];
+    text.push(b'b');
+    let patterns = (0..STACK_NEXT_SIZE).map(|len| {
+        let mut pattern = vec![b'a'; len];
+        if let Some(last) = pattern.last_mut() {
+            *last = b'b';
+        }
+        pattern
+    }).collect::<Vec<_>>();
+
+    for pattern in &patterns {
+        let (result, allocations) = count_allocations(|| knuth_morris_pratt(&text, pattern));
+        assert_eq!(allocations, 0, "pattern of length {}", pattern.len());
+        assert!(result.is_some());
+
+        let (_, allocations) = count_allocations(|| knuth_morris_pratt_rev(&text, pattern));
+        assert_eq!(allocations, 0, "pattern of length {}", pattern.len());
+        let (_, allocations) = count_allocations(|| knuth_morris_pratt_count(&text, pattern));
+        assert_eq!(allocations, 0, "pattern of length {}", pattern.len());
+        let (_, allocations) = count_allocations(|| {
+            knuth_morris_pratt_all(&text, pattern).sum::<usize>()
+        });
+        assert_eq!(allocations, 0, "pattern of length {}", pattern.len());
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_long_pattern_allocates() {
+    // check that the allocator counts: a long pattern needs a heap table
+    let text = vec![b'a'; 1000];
+    let pattern = vec![b'a'; 100];
+    let (result, allocations) = count_allocations(|| knuth_morris_pratt(&text, &pattern));
+    assert_eq!(result, Some(0));
+    assert!(allocations > 0);
+}
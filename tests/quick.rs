@@ -1,4 +1,7 @@
 
+//! The properties search for arbitrary patterns, many of them too long for
+//! the stack table, so they need the alloc feature.
+#![cfg(feature = "alloc")]
 #![allow(dead_code)]
 
 extern crate knuth_morris_pratt;
@@ -10,6 +13,43 @@ extern crate odds;
 
 
 use knuth_morris_pratt::knuth_morris_pratt;
+use knuth_morris_pratt::knuth_morris_pratt_all;
+use knuth_morris_pratt::knuth_morris_pratt_all_by;
+use knuth_morris_pratt::knuth_morris_pratt_any;
+#[cfg(feature = "memchr")]
+use knuth_morris_pratt::knuth_morris_pratt_bytes;
+#[cfg(feature = "memchr")]
+use knuth_morris_pratt::knuth_morris_pratt_bytes_ascii_ci;
+use knuth_morris_pratt::knuth_morris_pratt_ascii_case_insensitive;
+#[cfg(feature = "std")]
+use knuth_morris_pratt::knuth_morris_pratt_bmh;
+use knuth_morris_pratt::knuth_morris_pratt_by;
+use knuth_morris_pratt::knuth_morris_pratt_contains;
+use knuth_morris_pratt::knuth_morris_pratt_count;
+use knuth_morris_pratt::knuth_morris_pratt_count_overlapping;
+use knuth_morris_pratt::knuth_morris_pratt_from;
+use knuth_morris_pratt::knuth_morris_pratt_instrumented;
+use knuth_morris_pratt::knuth_morris_pratt_iter;
+use knuth_morris_pratt::knuth_morris_pratt_last;
+use knuth_morris_pratt::knuth_morris_pratt_longest_prefix_match;
+use knuth_morris_pratt::knuth_morris_pratt_match_indices;
+use knuth_morris_pratt::knuth_morris_pratt_overlapping;
+use knuth_morris_pratt::knuth_morris_pratt_overlapping_rmatches;
+use knuth_morris_pratt::knuth_morris_pratt_replace;
+use knuth_morris_pratt::knuth_morris_pratt_rev;
+use knuth_morris_pratt::knuth_morris_pratt_rev_by;
+use knuth_morris_pratt::knuth_morris_pratt_rmatches;
+use knuth_morris_pratt::knuth_morris_pratt_rsplit;
+use knuth_morris_pratt::knuth_morris_pratt_slices;
+use knuth_morris_pratt::knuth_morris_pratt_split;
+use knuth_morris_pratt::knuth_morris_pratt_str;
+use knuth_morris_pratt::knuth_morris_pratt_windows_matching;
+use knuth_morris_pratt::Searcher;
+use knuth_morris_pratt::SearchBuilder;
+#[cfg(feature = "testing")]
+use knuth_morris_pratt::naive_search;
+use knuth_morris_pratt::Automaton;
+use knuth_morris_pratt::KmpPattern;
 use std::ops::Deref;
 
 use odds::string::StrExt;
@@ -36,7 +76,7 @@ impl<T> Arbitrary for Short<T>
         Short(T::arbitrary(&mut qc::StdGen::new(g, sz)))
     }
 
-    fn shrink(&self) -> Box<Iterator<Item=Self>> {
+    fn shrink(&self) -> Box<dyn Iterator<Item=Self>> {
         Box::new((**self).shrink().map(Short))
     }
 }
@@ -46,8 +86,8 @@ macro_attr! {
     struct Text(String);
 }
 
-static ALPHABET: &'static str = "abñòαβ\u{3c72}";
-static SIMPLEALPHABET: &'static str = "ab";
+static ALPHABET: &str = "abñòαβ\u{3c72}";
+static SIMPLEALPHABET: &str = "ab";
 
 impl Arbitrary for Text {
     fn arbitrary<G: qc::Gen>(g: &mut G) -> Self {
@@ -61,12 +101,12 @@ impl Arbitrary for Text {
         }
         Text(s)
     }
-    fn shrink(&self) -> Box<Iterator<Item=Self>> {
+    fn shrink(&self) -> Box<dyn Iterator<Item=Self>> {
         Box::new(self.0.shrink().map(Text))
     }
 }
 
-/// Text from an alphabet of only two letters
+// Text from an alphabet of only two letters
 macro_attr! {
     #[derive(Clone, Debug, NewtypeDeref!)]
     struct SimpleText(String);
@@ -84,7 +124,7 @@ impl Arbitrary for SimpleText {
         }
         SimpleText(s)
     }
-    fn shrink(&self) -> Box<Iterator<Item=Self>> {
+    fn shrink(&self) -> Box<dyn Iterator<Item=Self>> {
         Box::new(self.0.shrink().map(SimpleText))
     }
 }
@@ -104,13 +144,13 @@ impl Arbitrary for ShortText {
         }
         ShortText(s)
     }
-    fn shrink(&self) -> Box<Iterator<Item=Self>> {
+    fn shrink(&self) -> Box<dyn Iterator<Item=Self>> {
         Box::new(self.0.shrink().map(ShortText))
     }
 }
 
 pub fn contains(hay: &str, n: &str) -> bool {
-    knuth_morris_pratt(hay.as_bytes(), n.as_bytes()).is_some()
+    knuth_morris_pratt_contains(hay.as_bytes(), n.as_bytes())
 }
 
 pub fn find(hay: &str, n: &str) -> Option<usize> {
@@ -118,13 +158,33 @@ pub fn find(hay: &str, n: &str) -> Option<usize> {
 }
 
 pub fn contains_rev(hay: &str, n: &str) -> bool {
-    let _ = (hay, n);
-    unimplemented!()
+    knuth_morris_pratt_rev(hay.as_bytes(), n.as_bytes()).is_some()
 }
 
 pub fn rfind(hay: &str, n: &str) -> Option<usize> {
-    let _ = (hay, n);
-    unimplemented!()
+    knuth_morris_pratt_rev(hay.as_bytes(), n.as_bytes())
+}
+
+// Reference search for any element type: compare at every offset
+fn naive_find_by<T, F>(text: &[T], pattern: &[T], from: usize, mut equal: F) -> Option<usize>
+    where F: FnMut(&T, &T) -> bool
+{
+    (from..text.len() + 1)
+        .filter(|&i| text.len() - i >= pattern.len())
+        .find(|&i| pattern.iter().zip(&text[i..]).all(|(p, t)| equal(p, t)))
+}
+
+// Reference for all non-overlapping matches
+fn naive_all_by<T, F>(text: &[T], pattern: &[T], mut equal: F) -> Vec<usize>
+    where F: FnMut(&T, &T) -> bool
+{
+    let mut matches = Vec::new();
+    let mut from = 0;
+    while let Some(i) = naive_find_by(text, pattern, from, &mut equal) {
+        matches.push(i);
+        from = i + pattern.len().max(1);
+    }
+    matches
 }
 
 #[test]
@@ -133,7 +193,7 @@ fn test_contains() {
         let a = &a.0;
         let b = &b[..];
         let truth = a.contains(b);
-        TestResult::from_bool(contains(&a, &b) == truth)
+        TestResult::from_bool(contains(a, b) == truth)
     }
     quickcheck(prop as fn(_, _) -> _);
 }
@@ -144,7 +204,42 @@ fn test_find_str() {
         let a = &a.0;
         let b = &b[..];
         let truth = a.find(b);
-        TestResult::from_bool(find(&a, &b) == truth)
+        TestResult::from_bool(find(a, b) == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_rfind_str() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.rfind(b);
+        TestResult::from_bool(rfind(a, b) == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_last_str() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let pat = [b, b].concat();
+        let last = |p: &str| knuth_morris_pratt_last(a.as_bytes(), p.as_bytes());
+        TestResult::from_bool(last(b) == a.rfind(b) && last(&pat) == a.rfind(&pat))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_rfind_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let pat = [b, b].concat();
+        let truth = a.rfind(&pat);
+        TestResult::from_bool(rfind(a, &pat) == truth)
     }
     quickcheck(prop as fn(_, _) -> _);
 }
@@ -155,10 +250,10 @@ fn test_contains_plus() {
         let a = &a.0;
         let b = &b[..];
         //let b = &b.0;
-        if b.len() == 0 { return TestResult::discard() }
+        if b.is_empty() { return TestResult::discard() }
         let truth = a.contains(b);
-        TestResult::from_bool(contains(&a, &b) == truth &&
-            (!truth || b.substrings().all(|sub| contains(&a, sub))))
+        TestResult::from_bool(contains(a, b) == truth &&
+            (!truth || b.substrings().all(|sub| contains(a, sub))))
     }
     quickcheck(prop as fn(_, _) -> _);
 }
@@ -183,7 +278,6 @@ fn test_contains_substrings() {
     quickcheck(prop as fn(_) -> _);
 }
 
-#[ignore]
 #[test]
 fn test_contains_substrings_rev() {
     fn prop(s: (char, char, char, char)) -> bool {
@@ -215,3 +309,648 @@ fn test_find_period() {
     }
     quickcheck(prop as fn(_, _) -> _);
 }
+
+#[test]
+fn test_find_all() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        if b.is_empty() { return TestResult::discard() }
+        let truth = a.match_indices(b).map(|(i, _)| i).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_all(a.as_bytes(), b.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_pattern_is_text() {
+    fn prop(a: Vec<i32>) -> TestResult {
+        let a = small_alphabet(&a);
+        let pat = a.clone();
+        let all = knuth_morris_pratt_all(&a, &pat).collect::<Vec<_>>();
+        let overlapping = knuth_morris_pratt_overlapping(&a, &pat).collect::<Vec<_>>();
+        TestResult::from_bool(knuth_morris_pratt(&a, &pat) == Some(0) &&
+                              knuth_morris_pratt_rev(&a, &pat) == Some(0) &&
+                              knuth_morris_pratt_count(&a, &pat) == 1 &&
+                              all == [0] && overlapping == [0])
+    }
+    quickcheck(prop as fn(_) -> _);
+}
+
+#[test]
+fn test_find_all_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        if b.is_empty() { return TestResult::discard() }
+        let truth = a.match_indices(b).map(|(i, _)| i).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_all(a.as_bytes(), b.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[cfg(feature = "memchr")]
+#[test]
+fn test_find_bytes() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        TestResult::from_bool(knuth_morris_pratt_bytes(a.as_bytes(), b.as_bytes()) == a.find(b))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[cfg(feature = "memchr")]
+#[test]
+fn test_find_bytes_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let pat = [b, b].concat();
+        TestResult::from_bool(knuth_morris_pratt_bytes(a.as_bytes(), pat.as_bytes()) == a.find(&pat))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[cfg(feature = "memchr")]
+#[test]
+fn test_find_bytes_ascii_ci() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, upper: Vec<bool>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        // randomly uppercase some letters of the text and the pattern
+        let mut upper = upper.into_iter().cycle();
+        let mut mixed_case = |s: &str| s.chars()
+            .map(|c| if upper.next() == Some(true) { c.to_ascii_uppercase() } else { c })
+            .collect::<String>();
+        let upper_a = mixed_case(a);
+        let upper_b = mixed_case(b);
+        let truth = upper_a.to_ascii_lowercase().find(&upper_b.to_ascii_lowercase());
+        let ours = knuth_morris_pratt_bytes_ascii_ci(upper_a.as_bytes(), upper_b.as_bytes());
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _, _) -> _);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_find_bmh() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        TestResult::from_bool(knuth_morris_pratt_bmh(a.as_bytes(), b.as_bytes()) == a.find(b))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_find_bmh_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let pat = [b, b].concat();
+        TestResult::from_bool(knuth_morris_pratt_bmh(a.as_bytes(), pat.as_bytes()) == a.find(&pat))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_find_all_back() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, front: Vec<bool>) -> TestResult {
+        let a = a.as_bytes();
+        let b = b.as_bytes();
+        let truth = knuth_morris_pratt_all(a, b).collect::<Vec<_>>();
+        let mut back = knuth_morris_pratt_all(a, b).rev().collect::<Vec<_>>();
+        back.reverse();
+        // alternate between the ends
+        let mut matches = knuth_morris_pratt_all(a, b);
+        let (mut head, mut tail) = (Vec::new(), Vec::new());
+        for &from_front in front.iter().cycle().take(truth.len() + 1) {
+            if from_front {
+                head.extend(matches.next());
+            } else {
+                tail.extend(matches.next_back());
+            }
+        }
+        head.extend(matches.by_ref());
+        tail.reverse();
+        head.extend(tail);
+        TestResult::from_bool(back == truth && head == truth)
+    }
+    quickcheck(prop as fn(_, _, _) -> _);
+}
+
+#[test]
+fn test_find_all_with_count() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, skip: u8) -> TestResult {
+        let a = a.as_bytes();
+        let b = b.as_bytes();
+        let mut matches = knuth_morris_pratt_all(a, b);
+        for _ in 0..skip % 4 {
+            matches.next();
+        }
+        let upper = matches.size_hint().1.unwrap();
+        let counted = matches.with_count();
+        let len = counted.len();
+        let count = counted.count();
+        TestResult::from_bool(len == count && count <= upper)
+    }
+    quickcheck(prop as fn(_, _, _) -> _);
+}
+
+#[test]
+fn test_find_slices() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, cuts: Vec<u16>) -> TestResult {
+        let a = a.as_bytes();
+        let b = &b[..];
+        let mut cuts = cuts.iter().map(|&cut| cut as usize % (a.len() + 1)).collect::<Vec<_>>();
+        cuts.push(0);
+        cuts.push(a.len());
+        cuts.sort();
+        let chunks = cuts.windows(2).map(|w| &a[w[0]..w[1]]).collect::<Vec<_>>();
+        let truth = knuth_morris_pratt(a, b.as_bytes());
+        TestResult::from_bool(knuth_morris_pratt_slices(&chunks, b.as_bytes()) == truth)
+    }
+    quickcheck(prop as fn(_, _, _) -> _);
+}
+
+#[test]
+fn test_searcher_find() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.find(b);
+        let searcher = Searcher::new(b.as_bytes().to_vec());
+        TestResult::from_bool(searcher.find(a.as_bytes()) == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_searcher_find_all() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.match_indices(b).map(|(i, _)| i).collect::<Vec<_>>();
+        let searcher = Searcher::new(b.as_bytes().to_vec());
+        let ours = searcher.find_all(a.as_bytes()).collect::<Vec<_>>();
+        // the searcher can be used again
+        let again = searcher.find_all(a.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth && again == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_count_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        if b.is_empty() { return TestResult::discard() }
+        let truth = a.matches(b).count();
+        TestResult::from_bool(knuth_morris_pratt_count(a.as_bytes(), b.as_bytes()) == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_overlapping_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        if b.is_empty() { return TestResult::discard() }
+        let truth = (0..a.len()).filter(|&i| a[i..].starts_with(b)).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_overlapping(a.as_bytes(), b.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_count_overlapping_invariant() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = a.0.as_bytes();
+        let b = b.as_bytes();
+        if b.is_empty() { return TestResult::discard() }
+        let count = knuth_morris_pratt_count(a, b);
+        let overlapping = knuth_morris_pratt_count_overlapping(a, b);
+        let truth = naive_all_by(a, b, u8::eq).len();
+        let overlapping_truth = (0..a.len()).filter(|&i| a[i..].starts_with(b)).count();
+        TestResult::from_bool(count <= overlapping &&
+                              count == truth &&
+                              overlapping == overlapping_truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_windows_matching_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = a.0.as_bytes();
+        let b = b.as_bytes();
+        if b.is_empty() { return TestResult::discard() }
+        let truth = a.windows(b.len()).map(|w| w == b).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_windows_matching(a, b).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_find_from() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, start: u16) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let start = start as usize;
+        if start > a.len() { return TestResult::discard() }
+        let truth = a[start..].find(b).map(|i| i + start);
+        TestResult::from_bool(knuth_morris_pratt_from(a.as_bytes(), b.as_bytes(), start) == truth)
+    }
+    quickcheck(prop as fn(_, _, _) -> _);
+}
+
+#[test]
+fn test_find_ascii_case_insensitive() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, upper: Vec<bool>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        // randomly uppercase some letters of the text
+        let upper_a = a.chars().enumerate()
+                       .map(|(i, c)| match upper.get(i) {
+                           Some(&true) => c.to_ascii_uppercase(),
+                           _ => c,
+                       })
+                       .collect::<String>();
+        let truth = a.find(b);
+        let ours = knuth_morris_pratt_ascii_case_insensitive(upper_a.as_bytes(), b.as_bytes());
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _, _) -> _);
+}
+
+// randomly uppercase some letters
+fn upper_some(s: &str, upper: &[bool]) -> String {
+    s.chars().zip(upper.iter().cycle())
+     .map(|(c, &up)| if up { c.to_ascii_uppercase() } else { c })
+     .collect()
+}
+
+#[test]
+fn test_find_by_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, len: u8, upper: Vec<bool>, upper_pat: Vec<bool>)
+        -> TestResult
+    {
+        if b.is_empty() || upper.is_empty() || upper_pat.is_empty() {
+            return TestResult::discard()
+        }
+        let a = &a.0;
+        // a pattern with period b.len(), of any length
+        let pat = b.chars().cycle().take(len as usize).collect::<String>();
+        let equal = u8::eq_ignore_ascii_case;
+        let upper_a = upper_some(a, &upper);
+        let upper_pat = upper_some(&pat, &upper_pat);
+        let (text, pattern) = (upper_a.as_bytes(), upper_pat.as_bytes());
+        let truth = a.find(&pat);
+        let naive = naive_find_by(text, pattern, 0, equal);
+        let ours = knuth_morris_pratt_by(text, pattern, equal);
+        let all = knuth_morris_pratt_all_by(text, pattern, equal).collect::<Vec<_>>();
+        let all_truth = naive_all_by(text, pattern, equal);
+        TestResult::from_bool(ours == truth && naive == truth && all == all_truth)
+    }
+    quickcheck(prop as fn(_, _, _, _, _) -> _);
+}
+
+#[test]
+fn test_rfind_by_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, len: u8, upper: Vec<bool>) -> TestResult {
+        if b.is_empty() || upper.is_empty() {
+            return TestResult::discard()
+        }
+        let a = &a.0;
+        let pat = b.chars().cycle().take(len as usize).collect::<String>();
+        let upper_pat = upper_some(&pat, &upper);
+        let ours = knuth_morris_pratt_rev_by(a.as_bytes(), upper_pat.as_bytes(),
+                                             u8::eq_ignore_ascii_case);
+        TestResult::from_bool(ours == a.rfind(&pat))
+    }
+    quickcheck(prop as fn(_, _, _, _) -> _);
+}
+
+#[test]
+fn test_kmp_pattern_match_indices() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.match_indices(b).collect::<Vec<_>>();
+        let ours = KmpPattern(b).match_indices(a).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth && KmpPattern(b).find(a) == a.find(b))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_knuth_morris_pratt_str() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        TestResult::from_bool(knuth_morris_pratt_str(a, b) == a.find(b))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[cfg(feature = "pattern")]
+#[test]
+fn test_kmp_pattern_str_methods() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        TestResult::from_bool(
+            a.find(KmpPattern(b)) == a.find(b) &&
+            a.match_indices(KmpPattern(b)).eq(a.match_indices(b)) &&
+            a.split(KmpPattern(b)).eq(a.split(b)) &&
+            a.replace(KmpPattern(b), "x") == a.replace(b, "x"))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_automaton() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        if b.is_empty() { return TestResult::discard() }
+        let truth = a.match_indices(b).map(|(i, _)| i + b.len()).collect::<Vec<_>>();
+        let mut automaton = Automaton::new(b.as_bytes().to_vec());
+        let ours = a.as_bytes().iter().filter_map(|x| automaton.push(x)).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_find_iter() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.find(b);
+        TestResult::from_bool(knuth_morris_pratt_iter(a.bytes(), b.as_bytes()) == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_find_any() {
+    fn prop(a: SimpleText, b: Vec<Short<SimpleText>>) -> TestResult {
+        let a = &a.0;
+        let patterns = b.iter().map(|p| p.as_bytes()).collect::<Vec<_>>();
+        let truth = b.iter().enumerate()
+                     .filter_map(|(index, p)| a.find(&p[..]).map(|start| (start, index)))
+                     .min();
+        TestResult::from_bool(knuth_morris_pratt_any(a.as_bytes(), &patterns) == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_replace_str() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, c: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        if b.is_empty() { return TestResult::discard() }
+        let truth = a.replace(b, &c);
+        let ours = knuth_morris_pratt_replace(a.as_bytes(), b.as_bytes(), c.as_bytes());
+        TestResult::from_bool(ours == truth.as_bytes())
+    }
+    quickcheck(prop as fn(_, _, _) -> _);
+}
+
+#[test]
+fn test_split_str() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.split(b).map(str::as_bytes).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_split(a.as_bytes(), b.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_match_indices_str() {
+    fn prop(a: Text, b: Short<Text>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        if b.is_empty() { return TestResult::discard() }
+        let truth = a.match_indices(b).map(|(i, s)| (i, s.as_bytes())).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_match_indices(a.as_bytes(), b.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+// Map arbitrary integers to a small alphabet, so that matches are likely
+fn small_alphabet(v: &[i32]) -> Vec<i32> {
+    v.iter().map(|x| x % 3).collect()
+}
+
+#[test]
+fn test_find_i32_offset() {
+    fn prop(a: Vec<i32>, b: Short<Vec<i32>>) -> TestResult {
+        let a = small_alphabet(&a);
+        let b = small_alphabet(&b);
+        TestResult::from_bool(match knuth_morris_pratt(&a, &b) {
+            Some(off) => a.get(off..off + b.len()) == Some(&b[..]),
+            None => naive_find_by(&a, &b, 0, i32::eq).is_none(),
+        })
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_find_i32_naive() {
+    fn prop(a: Vec<i32>, b: Short<Vec<i32>>) -> TestResult {
+        let a = small_alphabet(&a);
+        let b = small_alphabet(&b);
+        TestResult::from_bool(knuth_morris_pratt(&a, &b) == naive_search(&a, &b))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_find_i32_period_naive() {
+    fn prop(a: Vec<i32>, b: Short<Vec<i32>>) -> TestResult {
+        let a = small_alphabet(&a);
+        let b = small_alphabet(&b);
+        let pat = [&b[..], &b[..]].concat();
+        TestResult::from_bool(knuth_morris_pratt(&a, &pat) == naive_search(&a, &pat))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_instrumented_period() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let pat = [b, b].concat();
+        let (index, stats) = knuth_morris_pratt_instrumented(a.as_bytes(), pat.as_bytes());
+        TestResult::from_bool(index == a.find(&pat) &&
+                              stats.comparisons <= 2 * (a.len() + pat.len()))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_rmatches_str() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.rmatch_indices(b).map(|(i, _)| i).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_rmatches(a.as_bytes(), b.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_rmatches_forward() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = a.as_bytes();
+        let b = b.as_bytes();
+        // the matches only agree if the pattern can not overlap itself
+        if (1..b.len()).any(|k| b[..k] == b[b.len() - k..]) {
+            return TestResult::discard()
+        }
+        let mut forward = knuth_morris_pratt_all(a, b).collect::<Vec<_>>();
+        forward.reverse();
+        let ours = knuth_morris_pratt_rmatches(a, b).collect::<Vec<_>>();
+        TestResult::from_bool(ours == forward)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_overlapping_rmatches() {
+    fn prop(a: Vec<bool>, b: Vec<bool>) -> TestResult {
+        // short patterns over the two-symbol alphabet, so that they match
+        let b = &b[..b.len() % 7];
+        let mut forward = knuth_morris_pratt_overlapping(&a, b).collect::<Vec<_>>();
+        forward.reverse();
+        let ours = knuth_morris_pratt_overlapping_rmatches(&a, b).collect::<Vec<_>>();
+        TestResult::from_bool(ours == forward)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_rsplit_str() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let truth = a.rsplit(b).map(str::as_bytes).collect::<Vec<_>>();
+        let ours = knuth_morris_pratt_rsplit(a.as_bytes(), b.as_bytes()).collect::<Vec<_>>();
+        TestResult::from_bool(ours == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_search_builder() {
+    fn prop(a: SimpleText, b: Short<SimpleText>, from: u8, reverse: bool) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let from = from as usize;
+        let rest = match a.get(from..) {
+            Some(rest) => rest,
+            None => return TestResult::discard(),
+        };
+        let truth = if reverse {
+            rest.rmatch_indices(b).map(|(i, _)| i + from).collect::<Vec<_>>()
+        } else {
+            rest.match_indices(b).map(|(i, _)| i + from).collect::<Vec<_>>()
+        };
+        let builder = SearchBuilder::new(a.as_bytes(), b.as_bytes()).from(from).reverse(reverse);
+        let ours = builder.clone().all().collect::<Vec<_>>();
+        let count = builder.clone().count();
+        let first = builder.first();
+        TestResult::from_bool(ours == truth && count == truth.len() &&
+                              first == truth.first().cloned())
+    }
+    quickcheck(prop as fn(_, _, _, _) -> _);
+}
+
+#[test]
+fn test_search_builder_overlapping() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = a.as_bytes();
+        let b = b.as_bytes();
+        let truth = knuth_morris_pratt_overlapping(a, b).collect::<Vec<_>>();
+        let ours = SearchBuilder::new(a, b).overlapping(true).all().collect::<Vec<_>>();
+        let mut rev = SearchBuilder::new(a, b).overlapping(true).reverse(true)
+                                              .all().collect::<Vec<_>>();
+        rev.reverse();
+        TestResult::from_bool(ours == truth && rev == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_find_by_i32() {
+    fn prop(a: Vec<i32>, b: Short<Vec<i32>>) -> TestResult {
+        // congruence modulo 3 on unrestricted integers; equal elements are
+        // mostly not identical
+        let equal = |x: &i32, y: &i32| x.rem_euclid(3) == y.rem_euclid(3);
+        let pat = [&b[..], &b[..]].concat();
+        TestResult::from_bool(
+            knuth_morris_pratt_by(&a, &b, equal) == naive_find_by(&a, &b, 0, equal) &&
+            knuth_morris_pratt_by(&a, &pat, equal) == naive_find_by(&a, &pat, 0, equal))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_find_all_i32() {
+    fn prop(a: Vec<i32>, b: Short<Vec<i32>>) -> TestResult {
+        let a = small_alphabet(&a);
+        let b = small_alphabet(&b);
+        let ours = knuth_morris_pratt_all(&a, &b).collect::<Vec<_>>();
+        let ours_by = knuth_morris_pratt_all_by(&a, &b, |x, y| x == y).collect::<Vec<_>>();
+        let truth = naive_all_by(&a, &b, |x, y| x == y);
+        TestResult::from_bool(ours == truth && ours_by == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_find_large_elements() {
+    fn prop(a: Vec<u8>, b: Short<Vec<u8>>) -> TestResult {
+        // elements larger than a register
+        let large = |v: &[u8]| v.iter().map(|&x| [(x % 2) as u64; 8]).collect::<Vec<_>>();
+        let a = large(&a);
+        let b = large(&b);
+        TestResult::from_bool(knuth_morris_pratt(&a, &b) == naive_find_by(&a, &b, 0, |x, y| x == y))
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}
+
+#[test]
+fn test_longest_prefix_match() {
+    fn prop(a: SimpleText, b: Short<SimpleText>) -> TestResult {
+        let a = &a.0;
+        let b = &b[..];
+        let (start, len) = knuth_morris_pratt_longest_prefix_match(a.as_bytes(), b.as_bytes());
+        // the longest prefix that str::find finds, and where
+        let truth = (0..b.len() + 1).rev()
+            .filter(|&n| b.is_char_boundary(n))
+            .filter_map(|n| a.find(&b[..n]).map(|start| (start, n)))
+            .next();
+        TestResult::from_bool(Some((start, len)) == truth)
+    }
+    quickcheck(prop as fn(_, _) -> _);
+}